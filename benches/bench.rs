@@ -1,5 +1,3 @@
-use std::{iter::FilterMap, slice};
-
 use criterion::{criterion_group, criterion_main, Criterion};
 use evolution::EntityList;
 use rand::{seq::SliceRandom, Fill, Rng};
@@ -12,6 +10,7 @@ trait List {
     fn get(&self, index: usize) -> Option<&Self::T>;
     fn insert(&mut self, entity: Self::T) -> usize;
     fn remove(&mut self, index: usize);
+    fn iter(&self) -> impl Iterator<Item = &Self::T>;
 }
 
 impl<T> List for Vec<T>
@@ -25,7 +24,7 @@ where
     }
 
     fn get(&self, index: usize) -> Option<&T> {
-        self.get(index)
+        <[T]>::get(self, index)
     }
 
     fn insert(&mut self, entity: T) -> usize {
@@ -36,6 +35,10 @@ where
     fn remove(&mut self, index: usize) {
         self.remove(index);
     }
+
+    fn iter(&self) -> impl Iterator<Item = &T> {
+        <[T]>::iter(self)
+    }
 }
 
 impl<T> List for EntityList<T>
@@ -59,6 +62,10 @@ where
     fn remove(&mut self, index: usize) {
         self.remove(index)
     }
+
+    fn iter(&self) -> impl Iterator<Item = &Self::T> {
+        self.iter()
+    }
 }
 
 enum Operation<const SIZE: usize> {
@@ -72,12 +79,15 @@ where
 {
     let mut rng = Pcg64Mcg::new(0);
     let mut list = L::new();
-    let data = (0..2 * len).map(|_| {
-        let mut item = [0u8; SIZE];
-        rng.fill(&mut item);
-        item
-    });
+    let data: Vec<_> = (0..2 * len)
+        .map(|_| {
+            let mut item = [0u8; SIZE];
+            rng.fill(&mut item);
+            item
+        })
+        .collect();
     let mut operations: Vec<_> = data
+        .into_iter()
         .map(Operation::Insert)
         .chain((0..len).map(|_| Operation::Remove(rng.gen())))
         .collect();
@@ -105,7 +115,7 @@ where
     let mut indices: Vec<_> = (0..len).map(|_| rng.gen_range(0..len)).collect();
     indices.shuffle(&mut rng);
 
-    c.bench_function(&format!("vec rem {size} {len}"), |b| {
+    c.bench_function(&format!("vec rem {SIZE} {len}"), |b| {
         b.iter(|| for _ in list.iter() {})
     });
 
@@ -119,8 +129,8 @@ where
 }
 
 pub fn bench(c: &mut Criterion) {
-    for n in [20, 30, 40] {
-        bench_fib(c, n);
+    for len in [100, 1000, 10000] {
+        bench_list::<EntityList<_>, 16>(c, len);
     }
 }
 
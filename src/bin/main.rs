@@ -1,40 +1,448 @@
 use std::collections::VecDeque;
-use std::time::Duration;
 
-use evolution::graphics::{self, Camera};
-use evolution::world::EntityData;
+use evolution::coloring::ColorScheme;
+use evolution::graphics::charts::{self, Series};
+use evolution::graphics::{self, Camera, Trails};
+use evolution::replay::Replay;
+use evolution::world::{
+    CreatureTrait, Entity, EntityData, EntityId, EnvironmentField, TerrainGrid,
+};
 use evolution::{
+    config::Graphics,
     world::{Location, Vector},
-    State,
+    Config, CreatureColoring, Event, Heatmap, HeatmapLayer, Histogram, Scheduler, State,
 };
+use macroquad::color::Color;
+use macroquad::math::Rect;
 use macroquad::prelude::{self as mq};
 use macroquad::text::{self, TextParams};
-use macroquad::{
-    camera,
-    color::{colors, Color},
-    window::clear_background,
-};
-use nalgebra::Vector4;
+use macroquad::{camera, window::clear_background};
+use strum::IntoEnumIterator;
 
-const SPEEDUP: Option<f64> = None;
-const FRAME_RATE: f64 = 60.;
+/// Most real seconds per frame spent ticking, so slow ticks do not stall rendering.
+const TICK_BUDGET: f64 = 0.75 / 60.;
+/// Most ticks run in one frame.
+const MAX_TICKS_PER_FRAME: usize = 1000;
+/// Factor the speed multiplier changes by per key press.
+const SPEED_STEP: f64 = 2.;
 
-fn draw_info(state: &State, tps: usize) {
-    let num_creatures = state
-        .entities()
-        .filter(|entity| entity.is_creature())
-        .count();
+/// Zoom factor per mouse wheel step or key press.
+const ZOOM_STEP: f32 = 1.1;
+/// Keyboard panning speed in pixels per second.
+const PAN_SPEED: f32 = 400.;
+/// How far in pixels the mouse may move between press and release for it to count as a click.
+const CLICK_TOLERANCE: f32 = 4.;
+/// Minimum radius in pixels around an entity that selects it when clicked.
+const PICK_RADIUS: f32 = 6.;
+/// Height in pixels of the timeline of the replay player.
+const TIMELINE_HEIGHT: f32 = 24.;
+
+/// Config overrides as a query string, e.g. `seed=3&world_width=200`.
+///
+/// On the web this is the query string of the page URL, provided by the plugin in `index.html`.
+#[cfg(target_arch = "wasm32")]
+fn config_query() -> String {
+    extern "C" {
+        fn evolution_query_length() -> u32;
+        fn evolution_query_copy(buffer: *mut u8, length: u32);
+    }
+    // SAFETY: The plugin writes exactly `length` bytes of UTF-8 into the buffer.
+    let bytes = unsafe {
+        let length = evolution_query_length();
+        let mut bytes = vec![0; length as usize];
+        evolution_query_copy(bytes.as_mut_ptr(), length);
+        bytes
+    };
+    String::from_utf8(bytes).unwrap_or_default()
+}
+
+/// Config overrides as a query string, e.g. `seed=3&world_width=200`.
+///
+/// Natively this is the command line arguments joined by `&`, so `main seed=3 world_width=200` works.
+#[cfg(not(target_arch = "wasm32"))]
+fn config_query() -> String {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let replay_index = args.iter().position(|arg| arg == "--replay");
+    args.iter()
+        .enumerate()
+        .filter(|&(index, _)| {
+            replay_index.is_none_or(|replay| index != replay && index != replay + 1)
+        })
+        .map(|(_, arg)| arg.as_str())
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// The replay file to play back instead of simulating, given as `--replay FILE`.
+#[cfg(not(target_arch = "wasm32"))]
+fn replay_path() -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--replay");
+    args.next()?;
+    args.next()
+}
+
+/// The web build cannot read files, so it never plays back replays.
+#[cfg(target_arch = "wasm32")]
+fn replay_path() -> Option<String> {
+    None
+}
 
+/// The size of the part of the screen showing the world, i.e. everything left of the info panel and above the charts.
+fn world_view_size(graphics: &Graphics) -> Vector {
+    let panel_width = if graphics.show_panel {
+        graphics.panel_width
+    } else {
+        0.
+    };
+    let chart_height = if graphics.show_charts {
+        graphics.chart_height
+    } else {
+        0.
+    };
+    graphics::screen_size() - Vector::new(panel_width, chart_height)
+}
+
+fn mouse_location() -> Location {
+    let (x, y) = mq::mouse_position();
+    Location::new(x, y)
+}
+
+fn is_in_world_view(graphics: &Graphics, screen_location: Location) -> bool {
+    let size = world_view_size(graphics);
+    screen_location.x() < size.x && screen_location.y() < size.y
+}
+
+/// An empty heatmap with the layer and resolution set in `graphics`.
+fn new_heatmap(config: &Config, graphics: &Graphics) -> Heatmap {
+    Heatmap::new(
+        config,
+        graphics.heatmap_layer,
+        graphics.heatmap_cell_size,
+        graphics.heatmap_half_life,
+    )
+}
+
+/// The coloring after `coloring` when cycling through energy, each trait, species and lineage.
+fn next_coloring(coloring: CreatureColoring) -> CreatureColoring {
+    let colorings: Vec<_> = std::iter::once(CreatureColoring::Energy)
+        .chain(CreatureTrait::iter().map(CreatureColoring::Trait))
+        .chain([CreatureColoring::Species, CreatureColoring::Lineage])
+        .collect();
+    let index = colorings
+        .iter()
+        .position(|&other| other == coloring)
+        .unwrap_or(0);
+    colorings[(index + 1) % colorings.len()]
+}
+
+/// How fast the simulation runs.
+struct Speed {
+    paused: bool,
+    /// Simulated seconds per real second.
+    multiplier: f64,
+    /// Tick as fast as possible, ignoring the multiplier.
+    uncapped: bool,
+    /// Whether a single tick was requested while paused.
+    step: bool,
+}
+
+impl Speed {
+    fn new() -> Self {
+        Self {
+            paused: false,
+            multiplier: 1.,
+            uncapped: true,
+            step: false,
+        }
+    }
+
+    fn handle_input(&mut self) {
+        if mq::is_key_pressed(mq::KeyCode::Space) {
+            self.paused = !self.paused;
+        }
+        if mq::is_key_pressed(mq::KeyCode::Period) {
+            self.paused = true;
+            self.step = true;
+        }
+        if mq::is_key_pressed(mq::KeyCode::RightBracket) {
+            self.multiplier *= SPEED_STEP;
+            self.uncapped = false;
+        }
+        if mq::is_key_pressed(mq::KeyCode::LeftBracket) {
+            self.multiplier /= SPEED_STEP;
+            self.uncapped = false;
+        }
+        if mq::is_key_pressed(mq::KeyCode::U) {
+            self.uncapped = !self.uncapped;
+        }
+    }
+
+    /// Real seconds between ticks. `None` if ticking as fast as possible.
+    fn seconds_per_tick(&self, config: &Config) -> Option<f64> {
+        (!self.uncapped).then(|| config.tick_length() as f64 / self.multiplier)
+    }
+
+    fn description(&self) -> String {
+        let speed = if self.uncapped {
+            "uncapped".to_string()
+        } else {
+            format!("{:.3}x", self.multiplier)
+        };
+        if self.paused {
+            format!("{speed} (paused)")
+        } else {
+            speed
+        }
+    }
+}
+
+/// How the world is viewed and which entity is selected.
+struct View {
+    /// Rendering parameters, including the toggles changed at runtime.
+    graphics: Graphics,
+    camera: Camera,
+    selected: Option<EntityId>,
+    follow: bool,
+    /// The trait shown in the charts.
+    chart_trait: CreatureTrait,
+    trails: Trails,
+    heatmap: Heatmap,
+    /// Where the mouse was pressed if it is currently dragging the world.
+    drag_start: Option<Location>,
+    /// Where the mouse was last frame if it is currently dragging the world.
+    drag_location: Option<Location>,
+}
+
+impl View {
+    fn new(config: &Config) -> Self {
+        Self {
+            graphics: config.graphics.clone(),
+            camera: Camera::view_whole_world(config, world_view_size(&config.graphics)),
+            selected: None,
+            follow: false,
+            chart_trait: CreatureTrait::MaxAcceleration,
+            trails: Trails::new(),
+            heatmap: new_heatmap(config, &config.graphics),
+            drag_start: None,
+            drag_location: None,
+        }
+    }
+
+    fn handle_input(&mut self, state: &State, frame_time: f32) {
+        let mouse = mouse_location();
+
+        // Zoom with the mouse wheel around the cursor.
+        let (_, wheel) = mq::mouse_wheel();
+        if wheel != 0. && is_in_world_view(&self.graphics, mouse) {
+            let factor = if wheel > 0. {
+                ZOOM_STEP
+            } else {
+                1. / ZOOM_STEP
+            };
+            self.camera.zoom_around(mouse, factor);
+        }
+
+        // Drag to pan and click to select.
+        if mq::is_mouse_button_pressed(mq::MouseButton::Left)
+            && is_in_world_view(&self.graphics, mouse)
+        {
+            self.drag_start = Some(mouse);
+            self.drag_location = Some(mouse);
+        }
+        if mq::is_mouse_button_released(mq::MouseButton::Left) {
+            if let Some(drag_start) = self.drag_start {
+                if (mouse - drag_start).norm() <= CLICK_TOLERANCE {
+                    self.select_at(state, mouse);
+                }
+            }
+        }
+        if !mq::is_mouse_button_down(mq::MouseButton::Left) {
+            self.drag_start = None;
+            self.drag_location = None;
+        }
+        if let Some(drag_location) = self.drag_location {
+            let delta = mouse - drag_location;
+            if delta != Vector::zeros() {
+                self.camera.pan(delta);
+                self.follow = false;
+            }
+            self.drag_location = Some(mouse);
+        }
+
+        // Keyboard shortcuts.
+        let mut key_pan = Vector::zeros();
+        if mq::is_key_down(mq::KeyCode::Left) || mq::is_key_down(mq::KeyCode::A) {
+            key_pan.x += 1.;
+        }
+        if mq::is_key_down(mq::KeyCode::Right) || mq::is_key_down(mq::KeyCode::D) {
+            key_pan.x -= 1.;
+        }
+        if mq::is_key_down(mq::KeyCode::Up) || mq::is_key_down(mq::KeyCode::W) {
+            key_pan.y += 1.;
+        }
+        if mq::is_key_down(mq::KeyCode::Down) || mq::is_key_down(mq::KeyCode::S) {
+            key_pan.y -= 1.;
+        }
+        if key_pan != Vector::zeros() {
+            self.camera.pan(key_pan * PAN_SPEED * frame_time);
+            self.follow = false;
+        }
+        let view_center = Location::ORIGIN + world_view_size(&self.graphics) / 2.;
+        if mq::is_key_pressed(mq::KeyCode::Equal) || mq::is_key_pressed(mq::KeyCode::KpAdd) {
+            self.camera.zoom_around(view_center, ZOOM_STEP);
+        }
+        if mq::is_key_pressed(mq::KeyCode::Minus) || mq::is_key_pressed(mq::KeyCode::KpSubtract) {
+            self.camera.zoom_around(view_center, 1. / ZOOM_STEP);
+        }
+        if mq::is_key_pressed(mq::KeyCode::Home) {
+            self.camera = Camera::view_whole_world(state.config(), world_view_size(&self.graphics));
+            self.follow = false;
+        }
+        if mq::is_key_pressed(mq::KeyCode::Tab) {
+            self.select_next_creature(state);
+        }
+        if mq::is_key_pressed(mq::KeyCode::F) {
+            self.follow = !self.follow && self.selected.is_some();
+        }
+        if mq::is_key_pressed(mq::KeyCode::T) {
+            self.chart_trait = CreatureTrait::iter()
+                .cycle()
+                .skip_while(|&creature_trait| creature_trait != self.chart_trait)
+                .nth(1)
+                .unwrap(); // The iterator is infinite.
+        }
+        if mq::is_key_pressed(mq::KeyCode::C) {
+            self.graphics.creature_coloring = next_coloring(self.graphics.creature_coloring);
+        }
+        if mq::is_key_pressed(mq::KeyCode::I) {
+            self.graphics.show_panel = !self.graphics.show_panel;
+        }
+        if mq::is_key_pressed(mq::KeyCode::H) {
+            self.graphics.show_charts = !self.graphics.show_charts;
+        }
+        if mq::is_key_pressed(mq::KeyCode::L) {
+            self.graphics.show_legend = !self.graphics.show_legend;
+        }
+        if mq::is_key_pressed(mq::KeyCode::Key1) {
+            self.graphics.show_trails = !self.graphics.show_trails;
+            self.trails.clear();
+        }
+        if mq::is_key_pressed(mq::KeyCode::Key2) {
+            self.graphics.show_velocities = !self.graphics.show_velocities;
+        }
+        if mq::is_key_pressed(mq::KeyCode::Key3) {
+            self.graphics.show_targets = !self.graphics.show_targets;
+        }
+        if mq::is_key_pressed(mq::KeyCode::Key4) {
+            self.graphics.show_sensing = !self.graphics.show_sensing;
+        }
+        if mq::is_key_pressed(mq::KeyCode::Key5) {
+            self.graphics.show_heatmap = !self.graphics.show_heatmap;
+            self.heatmap.clear();
+        }
+        if mq::is_key_pressed(mq::KeyCode::Key6) {
+            self.graphics.heatmap_layer = HeatmapLayer::iter()
+                .cycle()
+                .skip_while(|&layer| layer != self.graphics.heatmap_layer)
+                .nth(1)
+                .unwrap(); // The iterator is infinite.
+            self.heatmap = new_heatmap(state.config(), &self.graphics);
+        }
+    }
+
+    /// Selects the entity under `screen_location`, or clears the selection if there is none.
+    fn select_at(&mut self, state: &State, screen_location: Location) {
+        let world_location = self.camera.camera_to_world(screen_location);
+        let min_radius = PICK_RADIUS / self.camera.zoom();
+        self.selected = state
+            .entities()
+            .map(|entity| (entity, (entity.location() - world_location).norm()))
+            .filter(|&(entity, distance)| {
+                distance <= (entity.size(state.config()) * 0.5).max(min_radius)
+            })
+            .min_by(|(_, distance1), (_, distance2)| distance1.total_cmp(distance2))
+            .map(|(entity, _)| entity.id());
+        if self.selected.is_none() {
+            self.follow = false;
+        }
+    }
+
+    /// Selects the creature with the smallest id larger than the currently selected one, wrapping around.
+    fn select_next_creature(&mut self, state: &State) {
+        let creature_ids = || {
+            state
+                .entities()
+                .filter(|entity| entity.is_creature())
+                .map(|entity| entity.id())
+        };
+        self.selected = self
+            .selected
+            .and_then(|selected| creature_ids().filter(|&id| id > selected).min())
+            .or_else(|| creature_ids().min());
+    }
+
+    /// Drops the selection if the entity no longer exists, keeps a followed entity in view and extends the trails.
+    fn update(&mut self, state: &State, scheduler: &Scheduler) {
+        if self.graphics.show_trails {
+            self.trails.record(state, self.graphics.trail_length);
+        }
+        if let Some(id) = self.selected {
+            match state.entity(id) {
+                Some(entity) if self.follow => {
+                    self.camera.center_on(
+                        scheduler.interpolated_location(entity),
+                        world_view_size(&self.graphics),
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    self.selected = None;
+                    self.follow = false;
+                }
+            }
+        }
+    }
+}
+
+/// Draws text in the info panel at the given distance in pixels from the top.
+fn draw_panel_text(graphics: &Graphics, text: &str, y: f32) {
     text::draw_text_ex(
-        &format!("Creatures: {num_creatures}"),
-        state.config().world_width() + 1.,
-        3.,
+        text,
+        world_view_size(graphics).x + 4.,
+        y,
         TextParams {
-            font_size: 16,
-            font_scale: 1. / 4.,
+            font_size: graphics.font_size,
+            color: graphics::vec_to_color(graphics.text_color),
             ..Default::default()
         },
     );
+}
+
+fn draw_line(graphics: &Graphics, text: &str, line: usize) {
+    draw_panel_text(graphics, text, graphics.line_height() * (line + 1) as f32);
+}
+
+fn draw_info(state: &State, view: &View, speed: &Speed, tps: usize) {
+    let graphics = &view.graphics;
+    if !graphics.show_panel {
+        return;
+    }
+    mq::draw_rectangle(
+        world_view_size(graphics).x,
+        0.,
+        graphics.panel_width,
+        mq::screen_height(),
+        graphics::vec_to_color(graphics.panel_color),
+    );
+
+    let num_creatures = state
+        .entities()
+        .filter(|entity| entity.is_creature())
+        .count();
+    let num_food = state.entities().filter(|entity| entity.is_food()).count();
+
+    draw_line(graphics, &format!("Creatures: {num_creatures}"), 0);
+    draw_line(graphics, &format!("Food: {num_food}"), 1);
     let avg_max_acceleration = state
         .entities()
         .filter_map(|entity| match entity.entity_data() {
@@ -43,155 +451,550 @@ fn draw_info(state: &State, tps: usize) {
         })
         .sum::<f32>()
         / num_creatures as f32;
-    text::draw_text_ex(
-        &format!("{avg_max_acceleration:.2}"),
-        state.config().world_width() + 1.,
-        7.,
-        TextParams {
-            font_size: 16,
-            font_scale: 1. / 4.,
-            ..Default::default()
-        },
-    );
-    text::draw_text_ex(
-        "Max acc. | Energy",
-        state.config().world_width() + 1.,
-        11.,
-        TextParams {
-            font_size: 16,
-            font_scale: 1. / 4.,
-            ..Default::default()
-        },
+    draw_line(
+        graphics,
+        &format!("Mean max acc.: {avg_max_acceleration:.2}"),
+        2,
     );
-    for (index, creature) in state
-        .entities()
-        .filter_map(|entity| match entity.entity_data() {
-            EntityData::Creature(creature) => Some(creature),
-            _ => None,
-        })
-        .enumerate()
+    if graphics.show_heatmap {
+        draw_line(graphics, &format!("Heatmap: {}", view.heatmap.layer()), 3);
+    }
+
+    match view.selected.and_then(|id| state.entity(id)) {
+        Some(entity) => draw_inspector(state, graphics, entity, view.follow, 4),
+        None => draw_line(graphics, "Click an entity to inspect it", 4),
+    }
+
+    for (index, line) in [
+        format!("Speed: {}", speed.description()),
+        format!("TPS: {tps: >6}"),
+    ]
+    .iter()
+    .enumerate()
     {
-        let energy = creature.energy();
-        let max_acceleration = creature.max_acceleration();
-        text::draw_text_ex(
-            &format!("{max_acceleration: >8.2} | {energy: >6.2}"),
-            state.config().world_width() + 1.,
-            15. + (index * 4) as f32,
-            TextParams {
-                font_size: 16,
-                font_scale: 1. / 4.,
-                ..Default::default()
-            },
+        draw_panel_text(
+            graphics,
+            line,
+            mq::screen_height() - 4. - graphics.line_height() * (1 - index) as f32,
         );
     }
+}
 
-    text::draw_text_ex(
-        &format!("TPS: {tps: >6}"),
-        state.config().world_width() + 1.,
-        state.config().world_height() - 1.,
-        TextParams {
-            font_size: 16,
-            font_scale: 1. / 4.,
-            ..Default::default()
-        },
+/// Draws the details of the selected entity starting at the given line.
+fn draw_inspector(
+    state: &State,
+    graphics: &Graphics,
+    entity: &Entity,
+    follow: bool,
+    first_line: usize,
+) {
+    let config = state.config();
+    let velocity = entity.velocity();
+    let mut lines = vec![
+        format!(
+            "{:?} {}{}",
+            entity.entity_type(),
+            entity.id(),
+            if follow { " (following)" } else { "" }
+        ),
+        format!(
+            "Location: ({:.1}, {:.1})",
+            entity.location().x(),
+            entity.location().y()
+        ),
+        format!(
+            "Velocity: ({:.2}, {:.2}) | {:.2}",
+            velocity.x,
+            velocity.y,
+            velocity.norm()
+        ),
+    ];
+    match entity.entity_data() {
+        EntityData::Creature(creature) => {
+            let lineage = creature.lineage();
+            let target = match creature.target() {
+                Some(target) => match state.entity(target) {
+                    Some(food) => format!(
+                        "{target} at ({:.1}, {:.1})",
+                        food.location().x(),
+                        food.location().y()
+                    ),
+                    None => format!("{target}"),
+                },
+                None => "none (wandering)".to_string(),
+            };
+            lines.extend([
+                format!(
+                    "Energy: {:.2} / {:.2}",
+                    creature.energy(),
+                    creature.max_energy(config)
+                ),
+                format!("Metabolism: {:.3}", creature.metabolic_cost().total()),
+                format!(
+                    "Age: {:.1} s{}",
+                    creature.age(),
+                    if creature.is_mature(config) {
+                        ""
+                    } else {
+                        " (immature)"
+                    }
+                ),
+                format!(
+                    "Temperature: {:.1}",
+                    state.environment_value(EnvironmentField::Temperature, entity.location())
+                ),
+                String::new(),
+                "Traits".to_string(),
+                format!("  Max acc.: {:.2}", creature.max_acceleration()),
+                format!("  Size: {:.2}", creature.size()),
+                format!("  Sensing range: {:.1}", creature.sensing_range()),
+                format!(
+                    "  Field of view: {:.0} deg",
+                    creature.field_of_view().to_degrees()
+                ),
+                format!("  Lifespan: {:.0} s", creature.lifespan()),
+                format!(
+                    "  Preferred temp.: {:.1} +- {:.1}",
+                    creature.preferred_temperature(),
+                    creature.temperature_tolerance()
+                ),
+                String::new(),
+                "Lineage".to_string(),
+                format!(
+                    "  Parent: {}",
+                    lineage
+                        .parent()
+                        .map_or_else(|| "none".to_string(), |parent| parent.to_string())
+                ),
+                format!("  Founder: {}", lineage.founder_id()),
+                format!("  Generation: {}", lineage.generation()),
+                String::new(),
+                format!("Target: {target}"),
+            ]);
+        }
+        EntityData::Food(food) => lines.push(format!("Energy: {:.2}", food.energy())),
+    }
+    for (index, line) in lines.iter().enumerate() {
+        draw_line(graphics, line, first_line + index);
+    }
+}
+
+fn draw_charts(state: &State, view: &View) {
+    let graphics = &view.graphics;
+    if !graphics.show_charts {
+        return;
+    }
+    let world_view_size = world_view_size(graphics);
+    let width = world_view_size.x / 3.;
+    let cell = |index: usize| {
+        Rect::new(
+            index as f32 * width,
+            world_view_size.y,
+            width,
+            graphics.chart_height,
+        )
+    };
+    let chart_color = graphics::vec_to_color(graphics.chart_color);
+
+    let samples: Vec<_> = state.history().samples().collect();
+    let creatures: Vec<_> = samples
+        .iter()
+        .map(|sample| sample.creatures() as f32)
+        .collect();
+    let food: Vec<_> = samples.iter().map(|sample| sample.food() as f32).collect();
+    charts::draw_line_chart(
+        cell(0),
+        "Population",
+        &[
+            Series {
+                label: "Creatures",
+                values: &creatures,
+                color: graphics::vec_to_color(graphics.creature_color),
+            },
+            Series {
+                label: "Food",
+                values: &food,
+                color: graphics::vec_to_color(graphics.food_color),
+            },
+        ],
+        true,
+        graphics,
+    );
+
+    let trait_means: Vec<_> = samples
+        .iter()
+        .map(|sample| sample.trait_mean(view.chart_trait))
+        .collect();
+    charts::draw_line_chart(
+        cell(1),
+        &format!("Mean {} (T to change)", view.chart_trait),
+        &[Series {
+            label: "Mean",
+            values: &trait_means,
+            color: chart_color,
+        }],
+        false,
+        graphics,
+    );
+
+    charts::draw_histogram(
+        cell(2),
+        &format!("{} distribution", view.chart_trait),
+        &Histogram::of_trait(state, view.chart_trait, graphics.histogram_bins),
+        chart_color,
+        graphics,
     );
 }
 
-#[macroquad::main("Evolution")]
-async fn main() {
-    let config = evolution::Config::default();
-    let mut state = State::init(config.clone(), 1);
+fn draw_world(state: &State, view: &View, scheduler: &Scheduler) {
+    let config = state.config();
+    let graphics = &view.graphics;
 
-    let camera = Camera::view_whole_world(&config, graphics::screen_size());
+    clear_background(graphics::vec_to_color(graphics.background_color));
+    camera::set_camera(&view.camera.mq_camera(graphics::screen_size()));
 
-    let seconds_per_tick = SPEEDUP.map(|speedup| config.tick_length() as f64 / speedup);
-    let mut next_tick_time = mq::get_time();
+    mq::draw_rectangle(
+        0.,
+        0.,
+        config.world_width(),
+        config.world_height(),
+        graphics::vec_to_color(graphics.world_color),
+    );
+    graphics::draw_terrain(state.terrain(), config, graphics);
 
-    let seconds_per_frame = 1. / FRAME_RATE;
-    let mut next_frame_time = mq::get_time();
+    let color_scheme = ColorScheme::new(state, graphics);
+    for entity in state.entities() {
+        let color = match entity.entity_data() {
+            EntityData::Creature(creature) => {
+                graphics::vec_to_color(color_scheme.creature_color(creature))
+            }
+            EntityData::Food(_) => graphics::vec_to_color(graphics.food_color),
+        };
 
-    let mut ticks_last_second = VecDeque::new();
+        let offsets = [Vector::new(0., 0.)];
+        for offset in offsets {
+            let location = scheduler.interpolated_location(entity)
+                + offset.component_mul(&(config.lower_right() - Location::ORIGIN));
+            mq::draw_circle(location.x(), location.y(), entity.size(config) * 0.5, color);
+        }
+    }
 
-    camera::set_camera(&camera.mq_camera(graphics::screen_size()));
+    let pixel = 1. / view.camera.zoom();
+    if graphics.show_trails {
+        view.trails.draw(graphics, 1.5 * pixel);
+    }
+    if graphics.show_heatmap {
+        graphics::draw_heatmap(&view.heatmap, graphics);
+    }
+    graphics::draw_overlays(state, graphics, pixel, |entity| {
+        scheduler.interpolated_location(entity)
+    });
 
-    loop {
-        let cur_time = mq::get_time();
+    // Highlight the selected entity.
+    if let Some(entity) = view.selected.and_then(|id| state.entity(id)) {
+        let location = scheduler.interpolated_location(entity);
+        mq::draw_circle_lines(
+            location.x(),
+            location.y(),
+            entity.size(config) * 0.5 + 3. * pixel,
+            2. * pixel,
+            graphics::vec_to_color(graphics.selection_color),
+        );
+    }
 
-        if mq::is_key_pressed(mq::KeyCode::Escape) {
-            break;
+    camera::set_default_camera();
+
+    if graphics.show_legend {
+        graphics::draw_legend(
+            &color_scheme.legend(state, graphics.legend_entries),
+            graphics.chart_margin,
+            graphics.chart_margin,
+            graphics,
+        );
+    }
+}
+
+/// Plays back a recorded run.
+struct Player {
+    replay: Replay,
+    /// The terrain of the recorded run, generated again from its config.
+    terrain: TerrainGrid,
+    camera: Camera,
+    /// The frame shown as a fractional frame index, so slow speeds advance smoothly.
+    position: f64,
+    paused: bool,
+    /// Simulated seconds per real second. Negative values play backwards.
+    multiplier: f64,
+    /// Where the mouse was last frame if it is currently dragging the world.
+    drag_location: Option<Location>,
+    /// Whether the mouse is dragging the timeline.
+    scrubbing: bool,
+}
+
+impl Player {
+    fn new(replay: Replay) -> Self {
+        let camera = Camera::view_whole_world(replay.config(), Self::world_view_size());
+        Self {
+            terrain: TerrainGrid::generate(replay.config()),
+            replay,
+            camera,
+            position: 0.,
+            paused: false,
+            multiplier: 1.,
+            drag_location: None,
+            scrubbing: false,
         }
-        while next_tick_time < next_frame_time {
-            ticks_last_second.push_front(cur_time);
-            while ticks_last_second
-                .back()
-                .map(|&t| cur_time - t > 1.)
-                .unwrap_or(false)
-            {
-                ticks_last_second.pop_back();
-            }
-            state.tick();
-            if let Some(seconds_per_tick) = seconds_per_tick {
-                next_tick_time += seconds_per_tick;
+    }
+
+    /// The size of the part of the screen showing the world, i.e. everything above the timeline.
+    fn world_view_size() -> Vector {
+        graphics::screen_size() - Vector::new(0., TIMELINE_HEIGHT)
+    }
+
+    fn last_index(&self) -> usize {
+        self.replay.frames().len().saturating_sub(1)
+    }
+
+    fn frame_index(&self) -> usize {
+        (self.position.round() as usize).min(self.last_index())
+    }
+
+    fn handle_input(&mut self, frame_time: f32) {
+        let mouse = mouse_location();
+        let in_world = mouse.y() < Self::world_view_size().y;
+
+        let (_, wheel) = mq::mouse_wheel();
+        if wheel != 0. && in_world {
+            let factor = if wheel > 0. {
+                ZOOM_STEP
             } else {
-                next_tick_time = mq::get_time();
+                1. / ZOOM_STEP
+            };
+            self.camera.zoom_around(mouse, factor);
+        }
+        if mq::is_mouse_button_pressed(mq::MouseButton::Left) {
+            if in_world {
+                self.drag_location = Some(mouse);
+            } else {
+                self.scrubbing = true;
             }
         }
+        if !mq::is_mouse_button_down(mq::MouseButton::Left) {
+            self.drag_location = None;
+            self.scrubbing = false;
+        }
+        if let Some(drag_location) = self.drag_location {
+            self.camera.pan(mouse - drag_location);
+            self.drag_location = Some(mouse);
+        }
+        if self.scrubbing {
+            let t = (mouse.x() / mq::screen_width()).clamp(0., 1.);
+            self.position = (t * self.last_index() as f32).round() as f64;
+        }
 
-        if next_frame_time > mq::get_time() {
-            std::thread::sleep(Duration::from_secs_f64(next_frame_time - mq::get_time()));
+        if mq::is_key_pressed(mq::KeyCode::Space) {
+            self.paused = !self.paused;
+        }
+        if mq::is_key_pressed(mq::KeyCode::Right) {
+            self.paused = true;
+            self.position = (self.frame_index() + 1).min(self.last_index()) as f64;
+        }
+        if mq::is_key_pressed(mq::KeyCode::Left) {
+            self.paused = true;
+            self.position = self.frame_index().saturating_sub(1) as f64;
+        }
+        if mq::is_key_pressed(mq::KeyCode::RightBracket) {
+            self.multiplier *= SPEED_STEP;
+        }
+        if mq::is_key_pressed(mq::KeyCode::LeftBracket) {
+            self.multiplier /= SPEED_STEP;
+        }
+        if mq::is_key_pressed(mq::KeyCode::B) {
+            self.multiplier = -self.multiplier;
+        }
+        if mq::is_key_pressed(mq::KeyCode::Home) {
+            self.position = 0.;
+        }
+        if mq::is_key_pressed(mq::KeyCode::End) {
+            self.position = self.last_index() as f64;
         }
 
-        clear_background(Color::new(0.3921, 0.5842, 0.9294, 1.0));
+        if !self.paused && !self.scrubbing {
+            let config = self.replay.config();
+            let seconds_per_frame = config.tick_length() as f64 * self.replay.interval() as f64;
+            self.position = (self.position
+                + frame_time as f64 * self.multiplier / seconds_per_frame)
+                .clamp(0., self.last_index() as f64);
+        }
+    }
 
-        for entity in state.entities() {
-            let color = match entity.entity_data() {
-                EntityData::Creature(creature) => graphics::vec_to_color(
-                    config.graphics.creature_color()
-                        + Vector4::new(
-                            0.,
-                            0.,
-                            0.,
-                            creature.energy() / config.creature_max_energy() - 1.,
-                        ),
-                ),
-                EntityData::Food => colors::GREEN,
-            };
+    fn draw(&self) {
+        let config = self.replay.config();
+        let graphics = &config.graphics;
+        clear_background(graphics::vec_to_color(graphics.background_color));
+        let Some(frame) = self.replay.frames().get(self.frame_index()) else {
+            return;
+        };
 
-            let offsets = [Vector::new(0., 0.)];
-            for offset in offsets {
-                let location = entity.location()
-                    + offset.component_mul(&(config.lower_right() - Location::ORIGIN));
-                mq::draw_circle(
-                    location.x(),
-                    location.y(),
-                    config.entity_size() * 0.5,
-                    color,
-                );
-            }
+        camera::set_camera(&self.camera.mq_camera(graphics::screen_size()));
+        mq::draw_rectangle(
+            0.,
+            0.,
+            config.world_width(),
+            config.world_height(),
+            graphics::vec_to_color(graphics.world_color),
+        );
+        graphics::draw_terrain(&self.terrain, config, graphics);
+        for entity in &frame.entities {
+            let [r, g, b, a] = entity.color;
+            mq::draw_circle(
+                entity.location.x(),
+                entity.location.y(),
+                entity.size * 0.5,
+                Color::from_rgba(r, g, b, a),
+            );
         }
+        // Mark births and deaths since the previous frame.
+        let pixel = 1. / self.camera.zoom();
+        for event in &frame.events {
+            let color = match event {
+                Event::Birth { .. } => graphics.selection_color,
+                Event::Death { .. } => graphics.background_color,
+                Event::Eaten { .. } => continue,
+            };
+            mq::draw_circle_lines(
+                event.location().x(),
+                event.location().y(),
+                config.entity_size(),
+                2. * pixel,
+                graphics::vec_to_color(color),
+            );
+        }
+        camera::set_default_camera();
 
-        let screen_edge = camera.camera_to_world(Location::ORIGIN + graphics::screen_size());
+        // Timeline.
+        let top = Self::world_view_size().y;
         mq::draw_rectangle(
-            config.world_width(),
             0.,
-            screen_edge.x() - config.world_width(),
-            screen_edge.y(),
-            mq::BLACK,
+            top,
+            mq::screen_width(),
+            TIMELINE_HEIGHT,
+            graphics::vec_to_color(graphics.panel_color),
         );
-
+        let t = self.frame_index() as f32 / self.last_index().max(1) as f32;
         mq::draw_rectangle(
             0.,
-            config.world_height(),
-            screen_edge.x(),
-            screen_edge.y() - config.world_height(),
-            mq::BLACK,
+            top,
+            mq::screen_width() * t,
+            TIMELINE_HEIGHT,
+            graphics::vec_to_color(graphics.chart_color),
+        );
+        let speed = if self.paused {
+            "paused".to_string()
+        } else {
+            format!("{}x", self.multiplier)
+        };
+        text::draw_text_ex(
+            format!(
+                "Tick {} / {}   Creatures: {}   Food: {}   Speed: {speed}",
+                frame.tick,
+                self.replay.frames().last().map_or(0, |frame| frame.tick),
+                frame.creatures(),
+                frame.food(),
+            ),
+            4.,
+            top + TIMELINE_HEIGHT - 6.,
+            TextParams {
+                font_size: graphics.font_size,
+                color: graphics::vec_to_color(graphics.text_color),
+                ..Default::default()
+            },
         );
+    }
+}
 
-        draw_info(&state, ticks_last_second.len());
+/// Plays back a replay until the window is closed.
+async fn play(replay: Replay) {
+    let mut player = Player::new(replay);
+    loop {
+        if mq::is_key_pressed(mq::KeyCode::Escape) {
+            break;
+        }
+        player.handle_input(mq::get_frame_time());
+        player.draw();
+        mq::next_frame().await
+    }
+}
+
+#[macroquad::main("Evolution")]
+async fn main() {
+    if let Some(path) = replay_path() {
+        match Replay::load(&path) {
+            Ok(replay) => return play(replay).await,
+            Err(error) => macroquad::logging::error!("Cannot load replay {path}: {error}"),
+        }
+    }
+    let mut config = evolution::Config::default();
+    if let Err(error) = config.apply_query(&config_query()) {
+        macroquad::logging::error!("Ignoring config overrides: {error}");
+        config = evolution::Config::default();
+    }
+    let mut state = State::init(config.clone(), 1);
+
+    let mut view = View::new(&config);
+
+    let mut speed = Speed::new();
+    let mut scheduler = Scheduler::new(
+        speed.seconds_per_tick(&config),
+        MAX_TICKS_PER_FRAME,
+        TICK_BUDGET,
+    );
+
+    let mut ticks_last_second = VecDeque::new();
+
+    loop {
+        let cur_time = mq::get_time();
+
+        if mq::is_key_pressed(mq::KeyCode::Escape) {
+            break;
+        }
+        view.handle_input(&state, mq::get_frame_time());
+        speed.handle_input();
+
+        let on_tick = |state: &State| {
+            ticks_last_second.push_front(cur_time);
+            if view.graphics.show_heatmap {
+                view.heatmap.record(state);
+            }
+        };
+        scheduler.set_seconds_per_tick(speed.seconds_per_tick(&config));
+        if speed.paused {
+            if speed.step {
+                scheduler.step(&mut state, on_tick);
+                speed.step = false;
+            }
+            scheduler.reset();
+        } else {
+            scheduler.frame(
+                &mut state,
+                mq::get_frame_time() as f64,
+                mq::get_time,
+                on_tick,
+            );
+        }
+        while ticks_last_second
+            .back()
+            .map(|&t| cur_time - t > 1.)
+            .unwrap_or(false)
+        {
+            ticks_last_second.pop_back();
+        }
+        view.update(&state, &scheduler);
 
-        camera::set_camera(&camera.mq_camera(graphics::screen_size()));
+        draw_world(&state, &view, &scheduler);
+        draw_info(&state, &view, &speed, ticks_last_second.len());
+        draw_charts(&state, &view);
 
-        next_frame_time += seconds_per_frame;
         mq::next_frame().await
     }
 }
@@ -0,0 +1,126 @@
+//! Runs the simulation without a window and writes frames as PNG images and optionally a replay.
+//!
+//! Usage: `render [--out DIR] [--ticks N] [--every N] [--width PIXELS] [--height PIXELS] [--creatures N]
+//! [--record FILE] [--record-every N] [KEY=VALUE]...`
+//!
+//! Frames are written to `DIR/frame_000000.png`, `DIR/frame_000001.png` and so on, every `--every` ticks and after the last
+//! tick. With `--every 0` only the last frame is written. `KEY=VALUE` arguments override the config like the query string
+//! of the web build, e.g. `seed=3 graphics.creature_coloring=Species`. A video can be made from the frames with e.g.
+//! `ffmpeg -framerate 30 -i DIR/frame_%06d.png video.mp4`.
+//!
+//! With `--record` a replay keeping every `--record-every`th tick and the last one is written to `FILE`, which can be
+//! played back with `main --replay FILE`.
+
+use std::{error::Error, path::PathBuf};
+
+use evolution::{render, replay::Replay, Config, Heatmap, State};
+
+/// Options of a rendering run that are not part of the config.
+struct Options {
+    out: PathBuf,
+    ticks: u64,
+    every: u64,
+    width: u32,
+    height: u32,
+    creatures: usize,
+    record: Option<PathBuf>,
+    record_every: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            out: PathBuf::from("frames"),
+            ticks: 24 * 60,
+            every: 24,
+            width: 800,
+            height: 800,
+            creatures: 1,
+            record: None,
+            record_every: 1,
+        }
+    }
+}
+
+/// Splits the command line arguments into options and config overrides.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<(Options, Config), Box<dyn Error>> {
+    let mut options = Options::default();
+    let mut config = Config::default();
+    while let Some(arg) = args.next() {
+        let Some(option) = arg.strip_prefix("--") else {
+            config.set_query(&arg)?;
+            continue;
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for --{option}"))?;
+        match option {
+            "out" => options.out = PathBuf::from(value),
+            "ticks" => options.ticks = value.parse()?,
+            "every" => options.every = value.parse()?,
+            "width" => options.width = value.parse()?,
+            "height" => options.height = value.parse()?,
+            "creatures" => options.creatures = value.parse()?,
+            "record" => options.record = Some(PathBuf::from(value)),
+            "record-every" => options.record_every = value.parse()?,
+            _ => return Err(format!("unknown option --{option}").into()),
+        }
+    }
+    config.validate()?;
+    Ok((options, config))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let (options, config) = parse_args(std::env::args().skip(1))?;
+    std::fs::create_dir_all(&options.out)?;
+
+    let graphics = config.graphics.clone();
+    let mut replay = options
+        .record
+        .is_some()
+        .then(|| Replay::new(config.clone(), options.record_every));
+    let mut state = State::init(config, options.creatures);
+    let mut heatmap = graphics.show_heatmap.then(|| {
+        Heatmap::new(
+            state.config(),
+            graphics.heatmap_layer,
+            graphics.heatmap_cell_size,
+            graphics.heatmap_half_life,
+        )
+    });
+
+    let mut frame = 0;
+    let mut write_frame = |state: &State, heatmap: Option<&Heatmap>| {
+        let path = options.out.join(format!("frame_{frame:06}.png"));
+        render::render(state, &graphics, heatmap, options.width, options.height).save_png(&path)?;
+        println!("tick {: >8}: {}", state.tick_count(), path.display());
+        frame += 1;
+        Ok::<_, Box<dyn Error>>(())
+    };
+
+    if options.every > 0 || options.ticks == 0 {
+        write_frame(&state, heatmap.as_ref())?;
+    }
+    if let Some(replay) = replay.as_mut() {
+        replay.record(&state, &graphics);
+    }
+    while state.tick_count() < options.ticks {
+        state.tick();
+        if let Some(heatmap) = heatmap.as_mut() {
+            heatmap.record(&state);
+        }
+        if let Some(replay) = replay.as_mut() {
+            replay.record(&state, &graphics);
+        }
+        let is_last = state.tick_count() == options.ticks;
+        if is_last || (options.every > 0 && state.tick_count().is_multiple_of(options.every)) {
+            write_frame(&state, heatmap.as_ref())?;
+        }
+    }
+    if let (Some(mut replay), Some(path)) = (replay, &options.record) {
+        replay.finish(&state, &graphics);
+        replay.save(path)?;
+        println!("replay: {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Runs a parameter sweep headlessly and writes a summary table.
+//!
+//! Usage: `sweep SPEC.json [--out TABLE.tsv] [--threads N] [KEY=VALUE]...`
+//!
+//! The spec is a JSON [`Sweep`], for example
+//!
+//! ```json
+//! {
+//!     "parameters": [
+//!         { "key": "drag", "range": { "Linear": { "min": 0.05, "max": 0.2, "steps": 4 } } },
+//!         { "key": "creature_reproduction_energy", "range": { "Values": ["50", "60", "70"] } }
+//!     ],
+//!     "mode": "Grid",
+//!     "seeds": [0, 1, 2],
+//!     "ticks": 14400,
+//!     "stop_conditions": ["Extinction", { "PopulationAbove": 500 }],
+//!     "creatures": 10
+//! }
+//! ```
+//!
+//! `KEY=VALUE` arguments override the base config of every run like the query string of the web build. The table is
+//! written to standard output unless `--out` is given.
+
+use std::{error::Error, fs::File, io::BufWriter, path::PathBuf};
+
+use evolution::{
+    sweep::{self, Sweep},
+    Config,
+};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut spec = None;
+    let mut out = None;
+    let mut threads = std::thread::available_parallelism().map_or(1, usize::from);
+    let mut config = Config::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out = Some(PathBuf::from(args.next().ok_or("missing value for --out")?)),
+            "--threads" => threads = args.next().ok_or("missing value for --threads")?.parse()?,
+            // Validated per run, once the sweep overrides are applied as well.
+            _ if arg.contains('=') => config.set_query(&arg)?,
+            _ if spec.is_none() => spec = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument {arg}").into()),
+        }
+    }
+    let spec = spec.ok_or("missing sweep spec")?;
+    let sweep: Sweep = serde_json::from_reader(File::open(spec)?)?;
+
+    let results = sweep.run(&config, threads)?;
+    match out {
+        Some(path) => sweep::write_table(&results, BufWriter::new(File::create(path)?))?,
+        None => sweep::write_table(&results, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
@@ -0,0 +1,356 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use nalgebra::Vector4;
+use strum::IntoEnumIterator;
+
+use crate::{
+    config::Graphics,
+    world::{Creature, CreatureTrait, EntityData},
+    Config, CreatureColoring, State,
+};
+
+/// Colors the gradient passes through from low to high values.
+const GRADIENT_STOPS: [[f32; 3]; 5] = [
+    [0.2, 0.2, 0.9],
+    [0.1, 0.8, 0.9],
+    [0.2, 0.9, 0.2],
+    [0.95, 0.9, 0.1],
+    [0.9, 0.1, 0.1],
+];
+/// Number of colors sampled for gradient legends.
+const LEGEND_GRADIENT_SAMPLES: usize = 16;
+
+/// Maps `t` in `[0, 1]` to a color on a blue to red gradient.
+pub fn gradient_color(t: f32) -> Vector4<f32> {
+    let t = if t.is_finite() { t.clamp(0., 1.) } else { 0.5 };
+    let position = t * (GRADIENT_STOPS.len() - 1) as f32;
+    let index = (position as usize).min(GRADIENT_STOPS.len() - 2);
+    let fraction = position - index as f32;
+    let [r0, g0, b0] = GRADIENT_STOPS[index];
+    let [r1, g1, b1] = GRADIENT_STOPS[index + 1];
+    Vector4::new(
+        r0 + (r1 - r0) * fraction,
+        g0 + (g1 - g0) * fraction,
+        b0 + (b1 - b0) * fraction,
+        1.,
+    )
+}
+
+/// A distinct color for each category key. Consecutive keys get very different hues.
+pub fn category_color(key: u64) -> Vector4<f32> {
+    const GOLDEN_RATIO_CONJUGATE: f32 = 0.618_034;
+    let hue = ((key % 65_521) as f32 * GOLDEN_RATIO_CONJUGATE).fract();
+    hsv_to_rgb(hue, 0.75, 0.95)
+}
+
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> Vector4<f32> {
+    let sector = hue * 6.;
+    let chroma = value * saturation;
+    let x = chroma * (1. - (sector % 2. - 1.).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    let m = value - chroma;
+    Vector4::new(r + m, g + m, b + m, 1.)
+}
+
+/// An entry of a categorical legend.
+#[derive(Debug, Clone)]
+pub struct LegendEntry {
+    pub label: String,
+    pub color: Vector4<f32>,
+    /// Number of creatures in the category.
+    pub count: usize,
+}
+
+/// Explains what the creature colors mean.
+#[derive(Debug, Clone)]
+pub enum Legend {
+    /// Colors sampled evenly from the value `min` to the value `max`.
+    Gradient {
+        label: String,
+        min: f32,
+        max: f32,
+        colors: Vec<Vector4<f32>>,
+    },
+    /// The most common categories, most common first.
+    Categories {
+        label: String,
+        entries: Vec<LegendEntry>,
+    },
+}
+
+/// Colors creatures according to the [`CreatureColoring`] of the graphics settings for a specific state.
+pub struct ColorScheme<'a> {
+    config: &'a Config,
+    graphics: &'a Graphics,
+    coloring: CreatureColoring,
+    /// Range of the colored trait in the population.
+    trait_range: (f32, f32),
+}
+
+impl<'a> ColorScheme<'a> {
+    pub fn new(state: &'a State, graphics: &'a Graphics) -> Self {
+        let coloring = graphics.creature_coloring;
+        let trait_range = match coloring {
+            CreatureColoring::Trait(creature_trait) => creatures(state)
+                .map(|creature| creature.trait_value(creature_trait))
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+                    (min.min(value), max.max(value))
+                }),
+            _ => (0., 1.),
+        };
+        Self {
+            config: state.config(),
+            graphics,
+            coloring,
+            trait_range,
+        }
+    }
+
+    pub fn coloring(&self) -> CreatureColoring {
+        self.coloring
+    }
+
+    pub fn creature_color(&self, creature: &Creature) -> Vector4<f32> {
+        match self.coloring {
+            CreatureColoring::Energy => {
+                self.energy_color(creature.energy() / creature.max_energy(self.config))
+            }
+            CreatureColoring::Trait(creature_trait) => {
+                let (min, max) = self.trait_range;
+                gradient_color(if max > min {
+                    (creature.trait_value(creature_trait) - min) / (max - min)
+                } else {
+                    0.5
+                })
+            }
+            CreatureColoring::Species => category_color(self.species(creature)),
+            CreatureColoring::Lineage => category_color(creature.lineage().founder_id().value()),
+        }
+    }
+
+    /// The creature color faded by the fraction of the maximum energy the creature has stored.
+    fn energy_color(&self, energy_fraction: f32) -> Vector4<f32> {
+        self.graphics.creature_color + Vector4::new(0., 0., 0., energy_fraction.clamp(0., 1.) - 1.)
+    }
+
+    /// Identifies the species of the creature by binning its traits under selection on a logarithmic grid.
+    ///
+    /// The preferred temperature can be zero or negative, so it is binned linearly in units of the starting tolerance.
+    pub fn species(&self, creature: &Creature) -> u64 {
+        let resolution = self.graphics.species_resolution;
+        let mut hasher = DefaultHasher::new();
+        for creature_trait in CreatureTrait::iter().filter(|t| t.is_selected(self.config)) {
+            let value = creature.trait_value(creature_trait);
+            let position = match creature_trait {
+                CreatureTrait::PreferredTemperature => {
+                    value / self.config.creature_starting_temperature_tolerance()
+                }
+                _ => value.ln(),
+            };
+            let bin = (position / resolution).floor() as i64;
+            bin.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Describes the colors in use, listing at most `max_entries` categories.
+    pub fn legend(&self, state: &State, max_entries: usize) -> Legend {
+        let gradient = |label: String, min: f32, max: f32, color: &dyn Fn(f32) -> Vector4<f32>| {
+            Legend::Gradient {
+                label,
+                min,
+                max,
+                colors: (0..LEGEND_GRADIENT_SAMPLES)
+                    .map(|index| color(index as f32 / (LEGEND_GRADIENT_SAMPLES - 1) as f32))
+                    .collect(),
+            }
+        };
+        match self.coloring {
+            CreatureColoring::Energy => {
+                gradient("Energy (fraction of max)".to_string(), 0., 1., &|t| {
+                    self.energy_color(t)
+                })
+            }
+            CreatureColoring::Trait(creature_trait) => {
+                let (min, max) = self.trait_range;
+                gradient(creature_trait.to_string(), min, max, &gradient_color)
+            }
+            CreatureColoring::Species => {
+                let entries = self
+                    .categories(state, |creature| self.species(creature))
+                    .into_iter()
+                    .take(max_entries)
+                    .enumerate()
+                    .map(|(rank, (species, count))| LegendEntry {
+                        label: format!("Species {}", rank + 1),
+                        color: category_color(species),
+                        count,
+                    })
+                    .collect();
+                Legend::Categories {
+                    label: "Species".to_string(),
+                    entries,
+                }
+            }
+            CreatureColoring::Lineage => Legend::Categories {
+                label: "Lineage".to_string(),
+                entries: self
+                    .categories(state, |creature| creature.lineage().founder_id().value())
+                    .into_iter()
+                    .take(max_entries)
+                    .map(|(founder, count)| LegendEntry {
+                        label: format!("Founder #{founder}"),
+                        color: category_color(founder),
+                        count,
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    /// Counts the creatures per category, most common first.
+    fn categories(&self, state: &State, key: impl Fn(&Creature) -> u64) -> Vec<(u64, usize)> {
+        let mut counts = HashMap::new();
+        for creature in creatures(state) {
+            *counts.entry(key(creature)).or_insert(0) += 1;
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|(key1, count1), (key2, count2)| count2.cmp(count1).then(key1.cmp(key2)));
+        counts
+    }
+}
+
+fn creatures(state: &State) -> impl Iterator<Item = &Creature> {
+    state
+        .entities()
+        .filter_map(|entity| match entity.entity_data() {
+            EntityData::Creature(creature) => Some(creature),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64Mcg;
+
+    use super::*;
+    use crate::world::{EntityIdGenerator, FieldLayout, Lineage};
+
+    /// A copy of the creature with the serialized field set to `value`.
+    fn with_field(creature: &Creature, field: &str, value: f32) -> Creature {
+        let mut json = serde_json::to_value(creature).unwrap();
+        json[field] = value.into();
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    pub fn gradient_color_is_clamped_to_its_ends() {
+        let stop = |index: usize| Vector3::from(GRADIENT_STOPS[index]);
+        let last = GRADIENT_STOPS.len() - 1;
+        let assert_color = |t: f32, expected: Vector3<f32>| {
+            let color = gradient_color(t);
+            assert!((color.xyz() - expected).norm() < 1e-6, "{t}: {color:?}");
+            assert_eq!(color.w, 1.);
+        };
+        assert_color(0., stop(0));
+        assert_color(1., stop(last));
+        assert_color(-3., stop(0));
+        assert_color(7., stop(last));
+        assert_color(f32::NAN, stop(last / 2));
+        // Halfway between the first two stops.
+        assert_color(0.5 / last as f32, (stop(0) + stop(1)) * 0.5);
+    }
+
+    #[test]
+    pub fn species_bins_traits() {
+        let mut config = Config::default();
+        config.environment.temperature.layout = FieldLayout::HorizontalGradient;
+        config.environment.temperature.amplitude = 10.;
+        let state = State::init(config.clone(), 0);
+        let graphics = Graphics::default();
+        let scheme = ColorScheme::new(&state, &graphics);
+        let id = EntityIdGenerator::new().next_id();
+        let creature = Creature::new(
+            &config,
+            Lineage::founder(id),
+            &mut Pcg64Mcg::seed_from_u64(0),
+        );
+        let species = scheme.species(&creature);
+
+        // Sizes are binned on a logarithmic grid with the species resolution as bin width.
+        let size = creature.size();
+        assert_eq!(
+            scheme.species(&with_field(&creature, "size", 1.01 * size)),
+            species
+        );
+        let resolution = graphics.species_resolution;
+        let larger = with_field(&creature, "size", resolution.exp() * size);
+        assert_ne!(scheme.species(&larger), species);
+
+        // The preferred temperature is binned linearly, so it can be zero or negative.
+        let tolerance = config.creature_starting_temperature_tolerance();
+        let step = resolution * tolerance;
+        let temperature =
+            |value| scheme.species(&with_field(&creature, "preferred_temperature", value));
+        assert_eq!(temperature(0.1 * step), temperature(0.9 * step));
+        assert_ne!(temperature(-0.1 * step), temperature(0.1 * step));
+        assert_eq!(temperature(-1.9 * step), temperature(-1.1 * step));
+
+        // Without temperature differences the preferred temperature does not tell species apart.
+        let uniform = State::init(Config::default(), 0);
+        let scheme = ColorScheme::new(&uniform, &graphics);
+        assert_eq!(
+            scheme.species(&with_field(&creature, "preferred_temperature", -50.)),
+            scheme.species(&creature)
+        );
+    }
+
+    #[test]
+    pub fn legend_lists_most_common_categories_first() {
+        let mut graphics = Graphics {
+            creature_coloring: CreatureColoring::Lineage,
+            ..Graphics::default()
+        };
+        let state = State::init(Config::default(), 5);
+        let Legend::Categories { entries, .. } =
+            ColorScheme::new(&state, &graphics).legend(&state, 3)
+        else {
+            panic!("lineages have a categorical legend");
+        };
+        // Every founder has one descendant, so ties are listed by founder id.
+        let labels: Vec<_> = entries.iter().map(|entry| entry.label.as_str()).collect();
+        assert_eq!(labels, ["Founder #0", "Founder #1", "Founder #2"]);
+
+        let mut config = Config::default();
+        config.set("creature_reproduction_energy", "5").unwrap();
+        let mut state = State::init(config, 10);
+        for _ in 0..100 {
+            state.tick();
+        }
+        graphics.creature_coloring = CreatureColoring::Species;
+        let Legend::Categories { entries, .. } =
+            ColorScheme::new(&state, &graphics).legend(&state, 8)
+        else {
+            panic!("species have a categorical legend");
+        };
+        // Descendants of the founders have drifted into more species than the legend lists.
+        assert_eq!(entries.len(), 8);
+        assert!(entries
+            .windows(2)
+            .all(|pair| pair[0].count >= pair[1].count));
+        assert!(entries[0].count > entries[7].count);
+        assert_eq!(entries[0].label, "Species 1");
+    }
+}
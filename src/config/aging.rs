@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// How creatures die of old age.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Mortality {
+    /// Creatures only die of starvation.
+    #[default]
+    Ageless,
+    /// Creatures die when they reach their lifespan.
+    Lifespan,
+    /// Creatures die at random with a rate that doubles every `doubling_time` seconds of age, as in the Gompertz
+    /// law. Both the rate and the doubling time are for the starting lifespan. Creatures with a longer lifespan age
+    /// proportionally slower.
+    Gompertz { base_rate: f32, doubling_time: f32 },
+}
+
+/// Parameters of aging and maturity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Aging {
+    pub mortality: Mortality,
+    /// Lifespan in seconds of the first creatures. Lifespan is heritable.
+    pub starting_lifespan: f32,
+    /// Seconds a creature has to live before it can reproduce.
+    pub maturity_age: f32,
+}
+
+impl Default for Aging {
+    fn default() -> Self {
+        Self {
+            mortality: Mortality::default(),
+            starting_lifespan: 600.,
+            maturity_age: 0.,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::world::{EnvironmentField, Field};
+
+/// The fields creatures live in.
+///
+/// By default both are uniform and constant, with light at 1 so food spawns at the configured rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Environment {
+    /// Temperature in arbitrary units. Creatures start out preferring its mean.
+    pub temperature: Field,
+    /// Light relative to the light at which food spawns at the configured rate. Negative light counts as none.
+    pub light: Field,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            temperature: Field::uniform(20.),
+            light: Field::uniform(1.),
+        }
+    }
+}
+
+impl Environment {
+    pub fn field(&self, field: EnvironmentField) -> &Field {
+        match field {
+            EnvironmentField::Temperature => &self.temperature,
+            EnvironmentField::Light => &self.light,
+        }
+    }
+}
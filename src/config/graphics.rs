@@ -1,11 +1,163 @@
 use nalgebra::Vector4;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct Graphics {}
+use crate::{
+    world::{CreatureTrait, Ground},
+    HeatmapLayer,
+};
+
+/// What determines the color of a creature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CreatureColoring {
+    /// The creature color, faded by how full the creature's energy store is.
+    #[default]
+    Energy,
+    /// A gradient over the range of the trait in the population.
+    Trait(CreatureTrait),
+    /// A distinct color per cluster of creatures with similar traits.
+    Species,
+    /// A distinct color per founder the creature descends from.
+    Lineage,
+}
+
+/// Rendering parameters. Colors are RGBA with components in `[0, 1]` and sizes are in pixels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Graphics {
+    /// Color of everything outside the world.
+    pub background_color: Vector4<f32>,
+    /// Color of the world itself, i.e. of open ground.
+    pub world_color: Vector4<f32>,
+    pub mud_color: Vector4<f32>,
+    pub water_color: Vector4<f32>,
+    pub obstacle_color: Vector4<f32>,
+    /// Base color of creatures when colored by energy.
+    pub creature_color: Vector4<f32>,
+    pub food_color: Vector4<f32>,
+    /// Color of the ring around the selected entity.
+    pub selection_color: Vector4<f32>,
+    pub panel_color: Vector4<f32>,
+    pub text_color: Vector4<f32>,
+    /// Color of trait means and histograms in the charts.
+    pub chart_color: Vector4<f32>,
+    /// Color of the newest part of motion trails. Older parts fade out.
+    pub trail_color: Vector4<f32>,
+    pub velocity_color: Vector4<f32>,
+    /// Color of lines from creatures to the food they are going for.
+    pub target_color: Vector4<f32>,
+    /// Color of the area creatures can sense.
+    pub sensing_color: Vector4<f32>,
+    pub chart_background_color: Vector4<f32>,
+    pub chart_border_color: Vector4<f32>,
+    /// Color of axis labels in the charts.
+    pub chart_label_color: Vector4<f32>,
+    pub legend_background_color: Vector4<f32>,
+    pub creature_coloring: CreatureColoring,
+    /// Width of a species cluster along each trait in log space.
+    ///
+    /// Creatures whose traits all differ by less than a factor of `exp(species_resolution)` tend to share a species.
+    pub species_resolution: f32,
+    /// Width of the info panel on the right.
+    pub panel_width: f32,
+    /// Height of the chart panel at the bottom.
+    pub chart_height: f32,
+    /// Font size of the info panel.
+    pub font_size: u16,
+    /// Font size of charts and the legend.
+    pub small_font_size: u16,
+    /// Space between the edges of charts and the legend and their contents.
+    pub chart_margin: f32,
+    pub legend_width: f32,
+    /// Height of the gradient in the legend and diameter of the color dots of its categories.
+    pub legend_swatch_size: f32,
+    /// Number of bins in the trait histogram.
+    pub histogram_bins: usize,
+    /// Maximum number of categories listed in the legend.
+    pub legend_entries: usize,
+    /// Number of frames of positions kept in motion trails.
+    pub trail_length: usize,
+    /// Seconds of movement shown by velocity arrows.
+    pub velocity_scale: f32,
+    pub heatmap_layer: HeatmapLayer,
+    /// Width and height of heatmap cells in world units.
+    pub heatmap_cell_size: f32,
+    /// Seconds of simulated time after which heatmap values have decayed to half.
+    pub heatmap_half_life: f32,
+    /// Opacity of the hottest heatmap cell.
+    pub heatmap_opacity: f32,
+    pub show_panel: bool,
+    pub show_charts: bool,
+    pub show_legend: bool,
+    pub show_trails: bool,
+    pub show_velocities: bool,
+    pub show_targets: bool,
+    pub show_sensing: bool,
+    pub show_heatmap: bool,
+}
+
+impl Default for Graphics {
+    fn default() -> Self {
+        Self {
+            background_color: Vector4::new(0., 0., 0., 1.),
+            world_color: Vector4::new(0.3921, 0.5842, 0.9294, 1.),
+            mud_color: Vector4::new(0.45, 0.33, 0.2, 1.),
+            water_color: Vector4::new(0.12, 0.3, 0.7, 1.),
+            obstacle_color: Vector4::new(0.25, 0.25, 0.25, 1.),
+            creature_color: Vector4::new(1., 0., 0., 1.),
+            food_color: Vector4::new(0., 0.89, 0.19, 1.),
+            selection_color: Vector4::new(0.99, 0.98, 0., 1.),
+            panel_color: Vector4::new(0., 0., 0., 1.),
+            text_color: Vector4::new(1., 1., 1., 1.),
+            chart_color: Vector4::new(1., 0.63, 0., 1.),
+            trail_color: Vector4::new(1., 1., 1., 0.6),
+            velocity_color: Vector4::new(1., 1., 0., 1.),
+            target_color: Vector4::new(0., 1., 0.3, 0.6),
+            sensing_color: Vector4::new(1., 1., 1., 0.3),
+            chart_background_color: Vector4::new(0.1, 0.1, 0.1, 1.),
+            chart_border_color: Vector4::new(0.51, 0.51, 0.51, 1.),
+            chart_label_color: Vector4::new(0.78, 0.78, 0.78, 1.),
+            legend_background_color: Vector4::new(0., 0., 0., 0.6),
+            creature_coloring: CreatureColoring::default(),
+            species_resolution: 0.25,
+            panel_width: 240.,
+            chart_height: 160.,
+            font_size: 16,
+            small_font_size: 14,
+            chart_margin: 4.,
+            legend_width: 170.,
+            legend_swatch_size: 10.,
+            histogram_bins: 20,
+            legend_entries: 8,
+            trail_length: 60,
+            velocity_scale: 1.,
+            heatmap_layer: HeatmapLayer::default(),
+            heatmap_cell_size: 5.,
+            heatmap_half_life: 10.,
+            heatmap_opacity: 0.8,
+            show_panel: true,
+            show_charts: true,
+            show_legend: true,
+            show_trails: false,
+            show_velocities: false,
+            show_targets: false,
+            show_sensing: false,
+            show_heatmap: false,
+        }
+    }
+}
 
 impl Graphics {
-    pub fn creature_color(&self) -> Vector4<f32> {
-        Vector4::new(1., 0., 0., 1.)
+    pub fn ground_color(&self, ground: Ground) -> Vector4<f32> {
+        match ground {
+            Ground::Open => self.world_color,
+            Ground::Mud => self.mud_color,
+            Ground::Water => self.water_color,
+            Ground::Obstacle => self.obstacle_color,
+        }
+    }
+
+    /// Distance between lines of text in the info panel.
+    pub fn line_height(&self) -> f32 {
+        self.font_size as f32 * 1.125
     }
 }
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// Parameters of the creature metabolism.
+///
+/// All rates are energy per second.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metabolism {
+    /// Cost of staying alive for a creature of the default entity size.
+    pub basal_rate: f32,
+    /// How the basal cost scales with size relative to the default entity size.
+    pub basal_size_exponent: f32,
+    /// Cost per unit of acceleration the creature applies.
+    pub acceleration_rate: f32,
+    /// Cost per unit of kinetic energy the creature has.
+    pub kinetic_energy_rate: f32,
+    /// Cost of sensing the surroundings for a creature with the starting sensing range and field of view.
+    /// Scales with the sensed area.
+    pub sensing_rate: f32,
+    /// Cost of living one temperature tolerance away from the preferred temperature. Grows with the square of the
+    /// distance.
+    pub temperature_rate: f32,
+    /// Cost of the starting temperature tolerance. Scales with the tolerance.
+    pub tolerance_rate: f32,
+    /// Cost of maintaining a body for the starting lifespan. Scales with the lifespan. Only charged if creatures can
+    /// die of old age.
+    pub lifespan_rate: f32,
+}
+
+impl Default for Metabolism {
+    fn default() -> Self {
+        Self {
+            basal_rate: 0.02,
+            basal_size_exponent: 1.5,
+            acceleration_rate: 0.02,
+            kinetic_energy_rate: 0.,
+            sensing_rate: 0.01,
+            temperature_rate: 0.02,
+            tolerance_rate: 0.005,
+            lifespan_rate: 0.005,
+        }
+    }
+}
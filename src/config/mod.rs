@@ -2,15 +2,27 @@ use serde::{Deserialize, Serialize};
 
 use crate::world::Location;
 
+mod aging;
+pub use aging::{Aging, Mortality};
+mod environment;
+pub use environment::Environment;
 mod graphics;
-use graphics::Graphics;
-
-const RNG_SEED: u128 = 0;
+pub use graphics::{CreatureColoring, Graphics};
+mod metabolism;
+pub use metabolism::Metabolism;
+mod physics;
+pub use physics::Physics;
+mod query;
+pub use query::ConfigError;
+mod reproduction;
+pub use reproduction::{Reproduction, ReproductionMode};
+mod terrain;
+pub use terrain::Terrain;
+
+const RNG_SEED: u64 = 0;
 
 const WORLD_WIDTH: f32 = 100.0;
 const WORLD_HEIGHT: f32 = 100.0;
-const UPPER_LEFT: Location = Location::new(0., 0.);
-const LOWER_RIGHT: Location = Location::new(WORLD_WIDTH, WORLD_HEIGHT);
 
 const TICK_LENGTH: f32 = 1. / 24.;
 
@@ -19,62 +31,203 @@ const DRAG: f32 = 0.1;
 const ENTITY_SIZE: f32 = 2.;
 
 const FOOD_SPAWN_RATE: f32 = 0.4;
-
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+const FOOD_ENERGY: f32 = 3.;
+
+const CREATURE_STARTING_ENERGY: f32 = 40.;
+const CREATURE_MAX_ENERGY: f32 = 100.;
+const CREATURE_REPRODUCTION_ENERGY: f32 = 60.;
+const CREATURE_CHILD_BOUNCE: f32 = 2.;
+
+const CREATURE_SENSING_RANGE: f32 = 25.;
+const CREATURE_FIELD_OF_VIEW: f32 = std::f32::consts::PI;
+/// Standard deviation of the change in heading of a wandering creature over one second.
+const CREATURE_WANDER_TURN_RATE: f32 = 1.;
+/// Fraction of its maximum force a wandering creature exerts.
+const CREATURE_WANDER_EFFORT: f32 = 0.25;
+/// How far from its preferred temperature a creature can live comfortably.
+const CREATURE_TEMPERATURE_TOLERANCE: f32 = 5.;
+
+const CREATURE_BODY_ENERGY: f32 = 20.;
+/// Fraction of a dead creature's energy left behind as a carcass. `None` disables carcasses.
+const CARCASS_ENERGY_FRACTION: Option<f32> = Some(0.5);
+
+/// Number of ticks between samples in the history.
+const HISTORY_INTERVAL: u64 = 24;
+/// Number of samples kept in the history.
+const HISTORY_LENGTH: usize = 600;
+
+/// Simulation parameters.
+///
+/// Every field can be overridden by name, see [`Config::set`] and [`Config::apply_query`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
+    rng_seed: u64,
+    world_width: f32,
+    world_height: f32,
+    tick_length: f32,
+    drag: f32,
+    entity_size: f32,
+    food_spawn_rate: f32,
+    food_energy: f32,
+    creature_starting_energy: f32,
+    creature_max_energy: f32,
+    creature_reproduction_energy: f32,
+    creature_child_bounce: f32,
+    creature_starting_sensing_range: f32,
+    creature_starting_field_of_view: f32,
+    creature_wander_turn_rate: f32,
+    creature_wander_effort: f32,
+    creature_starting_temperature_tolerance: f32,
+    creature_body_energy: f32,
+    creature_carcass_energy_fraction: Option<f32>,
+    history_interval: u64,
+    history_length: usize,
+    pub aging: Aging,
+    pub environment: Environment,
     pub graphics: Graphics,
+    pub metabolism: Metabolism,
+    pub physics: Physics,
+    pub reproduction: Reproduction,
+    pub terrain: Terrain,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rng_seed: RNG_SEED,
+            world_width: WORLD_WIDTH,
+            world_height: WORLD_HEIGHT,
+            tick_length: TICK_LENGTH,
+            drag: DRAG,
+            entity_size: ENTITY_SIZE,
+            food_spawn_rate: FOOD_SPAWN_RATE,
+            food_energy: FOOD_ENERGY,
+            creature_starting_energy: CREATURE_STARTING_ENERGY,
+            creature_max_energy: CREATURE_MAX_ENERGY,
+            creature_reproduction_energy: CREATURE_REPRODUCTION_ENERGY,
+            creature_child_bounce: CREATURE_CHILD_BOUNCE,
+            creature_starting_sensing_range: CREATURE_SENSING_RANGE,
+            creature_starting_field_of_view: CREATURE_FIELD_OF_VIEW,
+            creature_wander_turn_rate: CREATURE_WANDER_TURN_RATE,
+            creature_wander_effort: CREATURE_WANDER_EFFORT,
+            creature_starting_temperature_tolerance: CREATURE_TEMPERATURE_TOLERANCE,
+            creature_body_energy: CREATURE_BODY_ENERGY,
+            creature_carcass_energy_fraction: CARCASS_ENERGY_FRACTION,
+            history_interval: HISTORY_INTERVAL,
+            history_length: HISTORY_LENGTH,
+            aging: Aging::default(),
+            environment: Environment::default(),
+            graphics: Graphics::default(),
+            metabolism: Metabolism::default(),
+            physics: Physics::default(),
+            reproduction: Reproduction::default(),
+            terrain: Terrain::default(),
+        }
+    }
 }
 
 impl Config {
-    pub fn rng_seed(&self) -> u128 {
-        RNG_SEED
+    /// Seed of the random number generator of the simulation.
+    ///
+    /// Expanded with `SeedableRng::seed_from_u64`, so nearby seeds such as 0 to 3 give independent runs.
+    pub fn rng_seed(&self) -> u64 {
+        self.rng_seed
     }
 
     pub fn world_width(&self) -> f32 {
-        WORLD_WIDTH
+        self.world_width
     }
 
     pub fn world_height(&self) -> f32 {
-        WORLD_HEIGHT
+        self.world_height
     }
 
     pub fn upper_left(&self) -> Location {
-        UPPER_LEFT
+        Location::ORIGIN
     }
 
     pub fn lower_right(&self) -> Location {
-        LOWER_RIGHT
+        Location::new(self.world_width, self.world_height)
     }
 
     pub fn tick_length(&self) -> f32 {
-        TICK_LENGTH
+        self.tick_length
     }
 
     pub fn drag(&self) -> f32 {
-        DRAG
+        self.drag
     }
 
     pub fn entity_size(&self) -> f32 {
-        ENTITY_SIZE
+        self.entity_size
     }
 
     pub fn creature_starting_energy(&self) -> f32 {
-        40.
+        self.creature_starting_energy
     }
 
+    /// The most energy a creature of unit mass can store.
     pub fn creature_max_energy(&self) -> f32 {
-        100.
+        self.creature_max_energy
     }
 
+    /// The energy above which a creature of unit mass reproduces.
     pub fn creature_reproduction_energy(&self) -> f32 {
-        60.
+        self.creature_reproduction_energy
     }
 
     pub fn creature_child_bounce(&self) -> f32 {
-        2.
+        self.creature_child_bounce
+    }
+
+    pub fn creature_starting_sensing_range(&self) -> f32 {
+        self.creature_starting_sensing_range
+    }
+
+    /// Starting field of view in radians.
+    pub fn creature_starting_field_of_view(&self) -> f32 {
+        self.creature_starting_field_of_view
+    }
+
+    pub fn creature_wander_turn_rate(&self) -> f32 {
+        self.creature_wander_turn_rate
+    }
+
+    pub fn creature_wander_effort(&self) -> f32 {
+        self.creature_wander_effort
+    }
+
+    /// How far from its preferred temperature a creature can live comfortably at the start. Creatures start out
+    /// preferring the mean temperature.
+    pub fn creature_starting_temperature_tolerance(&self) -> f32 {
+        self.creature_starting_temperature_tolerance
+    }
+
+    /// Energy bound up in a creature's body. Not available to the creature itself, but part of it is left behind in its carcass.
+    pub fn creature_body_energy(&self) -> f32 {
+        self.creature_body_energy
+    }
+
+    pub fn creature_carcass_energy_fraction(&self) -> Option<f32> {
+        self.creature_carcass_energy_fraction
     }
 
     pub fn food_spawn_rate(&self) -> f32 {
-        FOOD_SPAWN_RATE
+        self.food_spawn_rate
+    }
+
+    pub fn food_energy(&self) -> f32 {
+        self.food_energy
+    }
+
+    /// Number of ticks between samples in the history.
+    pub fn history_interval(&self) -> u64 {
+        self.history_interval
+    }
+
+    /// Number of samples kept in the history.
+    pub fn history_length(&self) -> usize {
+        self.history_length
     }
 }
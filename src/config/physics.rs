@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use crate::world::Integrator;
+
+/// Parameters of the movement of entities.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Physics {
+    pub integrator: Integrator,
+    /// Number of integration steps per tick.
+    pub sub_steps: u32,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Self {
+            integrator: Integrator::default(),
+            sub_steps: 1,
+        }
+    }
+}
@@ -0,0 +1,345 @@
+use std::{f32::consts::TAU, fmt};
+
+use serde_json::Value;
+
+use strum::IntoEnumIterator;
+
+use super::{Config, Mortality, ReproductionMode};
+use crate::world::{EnvironmentField, FieldLayout};
+
+/// Shorter names accepted in place of field paths.
+const ALIASES: [(&str, &str); 1] = [("seed", "rng_seed")];
+
+/// Why a config field could not be overridden.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// No field has the given path.
+    UnknownKey(String),
+    /// The value does not fit the type of the field.
+    InvalidValue {
+        key: String,
+        value: String,
+        message: String,
+    },
+    /// A value of the config is outside the range the simulation can handle.
+    OutOfRange(String),
+    /// A query parameter is not of the form `key=value` or is not properly percent-encoded.
+    MalformedQuery(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => write!(f, "unknown config key `{key}`"),
+            ConfigError::InvalidValue {
+                key,
+                value,
+                message,
+            } => write!(f, "invalid value `{value}` for `{key}`: {message}"),
+            ConfigError::OutOfRange(message) => write!(f, "invalid config: {message}"),
+            ConfigError::MalformedQuery(parameter) => {
+                write!(f, "malformed query parameter `{parameter}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Overrides the field at the dot-separated `key`, e.g. `world_width` or `metabolism.basal_rate`.
+    ///
+    /// `value` is parsed as JSON, falling back to a plain string so enum variants like `Species` need no quotes. Only
+    /// the type is checked, so that fields which constrain each other can be set in any order. Call
+    /// [`Config::validate`] once all fields are set.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let path = ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == key)
+            .map_or(key, |(_, path)| path);
+        let mut root =
+            serde_json::to_value(&*self).expect("Config is always representable as JSON");
+        let mut field = &mut root;
+        for part in path.split('.') {
+            field = field
+                .as_object_mut()
+                .and_then(|object| object.get_mut(part))
+                .ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
+        }
+        *field = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+        *self = serde_json::from_value(root).map_err(|error| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            message: error.to_string(),
+        })?;
+        Ok(())
+    }
+
+    /// Checks that every value is in the range the simulation can handle, describing the first one that is not.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.check_ranges().map_err(ConfigError::OutOfRange)
+    }
+
+    fn check_ranges(&self) -> Result<(), String> {
+        let metabolism = &self.metabolism;
+        for (name, value) in [
+            ("world_width", self.world_width),
+            ("world_height", self.world_height),
+            ("tick_length", self.tick_length),
+            ("entity_size", self.entity_size),
+            ("creature_starting_energy", self.creature_starting_energy),
+            ("creature_max_energy", self.creature_max_energy),
+            (
+                "creature_starting_temperature_tolerance",
+                self.creature_starting_temperature_tolerance,
+            ),
+        ] {
+            positive(name, value)?;
+        }
+        for (name, value) in [
+            ("drag", self.drag),
+            ("food_spawn_rate", self.food_spawn_rate),
+            ("food_energy", self.food_energy),
+            (
+                "creature_reproduction_energy",
+                self.creature_reproduction_energy,
+            ),
+            ("creature_child_bounce", self.creature_child_bounce),
+            (
+                "creature_starting_sensing_range",
+                self.creature_starting_sensing_range,
+            ),
+            ("creature_wander_turn_rate", self.creature_wander_turn_rate),
+            ("creature_wander_effort", self.creature_wander_effort),
+            ("creature_body_energy", self.creature_body_energy),
+            ("metabolism.basal_rate", metabolism.basal_rate),
+            ("metabolism.acceleration_rate", metabolism.acceleration_rate),
+            (
+                "metabolism.kinetic_energy_rate",
+                metabolism.kinetic_energy_rate,
+            ),
+            ("metabolism.sensing_rate", metabolism.sensing_rate),
+            ("metabolism.temperature_rate", metabolism.temperature_rate),
+            ("metabolism.tolerance_rate", metabolism.tolerance_rate),
+        ] {
+            non_negative(name, value)?;
+        }
+        if !metabolism.basal_size_exponent.is_finite() {
+            return Err("`metabolism.basal_size_exponent` must be finite".to_string());
+        }
+        positive(
+            "creature_starting_field_of_view",
+            self.creature_starting_field_of_view,
+        )?;
+        if self.creature_starting_field_of_view > TAU {
+            return Err("`creature_starting_field_of_view` must be at most 2π".to_string());
+        }
+        if let Some(fraction) = self.creature_carcass_energy_fraction {
+            if !(0. ..=1.).contains(&fraction) {
+                return Err(
+                    "`creature_carcass_energy_fraction` must be between 0 and 1".to_string()
+                );
+            }
+        }
+        for field in EnvironmentField::iter() {
+            let settings = self.environment.field(field);
+            let name = field.to_string().to_lowercase();
+            if ![settings.mean, settings.amplitude, settings.cycle_amplitude]
+                .iter()
+                .all(|value| value.is_finite())
+            {
+                return Err(format!("`environment.{name}` must be finite"));
+            }
+            non_negative(&format!("environment.{name}.period"), settings.period)?;
+            if let FieldLayout::Waves { wavelength } = settings.layout {
+                positive(&format!("environment.{name}.layout.wavelength"), wavelength)?;
+            }
+        }
+        let terrain = &self.terrain;
+        positive("terrain.cell_size", terrain.cell_size)?;
+        for (name, value) in [
+            ("terrain.mud_fraction", terrain.mud_fraction),
+            ("terrain.water_fraction", terrain.water_fraction),
+            ("terrain.obstacle_fraction", terrain.obstacle_fraction),
+            ("terrain.mud_drag", terrain.mud_drag),
+            ("terrain.water_drag", terrain.water_drag),
+        ] {
+            non_negative(name, value)?;
+        }
+        if terrain.mud_fraction + terrain.water_fraction + terrain.obstacle_fraction > 1. {
+            return Err("terrain fractions must not add up to more than 1".to_string());
+        }
+        let reproduction = &self.reproduction;
+        let min_litter_size = match reproduction.mode {
+            // A litter of one would just reset the creature's age.
+            ReproductionMode::Fission => 2,
+            ReproductionMode::Budding => 1,
+        };
+        if reproduction.litter_size < min_litter_size {
+            return Err(format!(
+                "`reproduction.litter_size` must be at least {min_litter_size} with {:?}",
+                reproduction.mode
+            ));
+        }
+        if !(0. ..=1.).contains(&reproduction.investment) {
+            return Err("`reproduction.investment` must be between 0 and 1".to_string());
+        }
+        non_negative("reproduction.cost", reproduction.cost)?;
+        let aging = &self.aging;
+        positive("aging.starting_lifespan", aging.starting_lifespan)?;
+        non_negative("aging.maturity_age", aging.maturity_age)?;
+        if let Mortality::Gompertz {
+            base_rate,
+            doubling_time,
+        } = aging.mortality
+        {
+            positive("aging.mortality.Gompertz.base_rate", base_rate)?;
+            positive("aging.mortality.Gompertz.doubling_time", doubling_time)?;
+        }
+        let graphics = &self.graphics;
+        positive("graphics.heatmap_cell_size", graphics.heatmap_cell_size)?;
+        for (name, value) in [
+            ("physics.sub_steps", self.physics.sub_steps as usize),
+            ("graphics.histogram_bins", graphics.histogram_bins),
+            ("history_interval", self.history_interval as usize),
+        ] {
+            if value == 0 {
+                return Err(format!("`{name}` must be at least 1"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies every `key=value` pair of a URL query string like `?seed=3&metabolism.basal_rate=0.05` with [`Config::set`].
+    ///
+    /// The result is validated once all pairs are applied. The config is left unchanged if any pair or the result is
+    /// invalid.
+    pub fn apply_query(&mut self, query: &str) -> Result<(), ConfigError> {
+        let mut config = self.clone();
+        config.set_query(query)?;
+        config.validate()?;
+        *self = config;
+        Ok(())
+    }
+
+    /// Like [`Config::apply_query`] but without validating, for overrides that are followed by more.
+    pub fn set_query(&mut self, query: &str) -> Result<(), ConfigError> {
+        let query = query.strip_prefix('?').unwrap_or(query);
+        for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+            let malformed = || ConfigError::MalformedQuery(parameter.to_string());
+            let (key, value) = parameter.split_once('=').ok_or_else(malformed)?;
+            let key = percent_decode(key).ok_or_else(malformed)?;
+            let value = percent_decode(value).ok_or_else(malformed)?;
+            self.set(&key, &value)?;
+        }
+        Ok(())
+    }
+}
+
+fn positive(name: &str, value: f32) -> Result<(), String> {
+    if value.is_finite() && value > 0. {
+        Ok(())
+    } else {
+        Err(format!("`{name}` must be positive and finite"))
+    }
+}
+
+fn non_negative(name: &str, value: f32) -> Result<(), String> {
+    if value.is_finite() && value >= 0. {
+        Ok(())
+    } else {
+        Err(format!("`{name}` must be non-negative and finite"))
+    }
+}
+
+/// Decodes `%XX` escapes and `+` as used in URL queries. `None` if the escapes are invalid or not UTF-8.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match byte {
+            b'%' => {
+                let hex = tail
+                    .get(..2)
+                    .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+                bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                rest = &tail[2..];
+            }
+            b'+' => {
+                bytes.push(b' ');
+                rest = tail;
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CreatureColoring;
+
+    #[test]
+    pub fn apply_query_sets_fields() {
+        let mut config = Config::default();
+        config
+            .apply_query("?seed=7&metabolism.basal_rate=0.5&graphics.creature_coloring=Species&creature_carcass_energy_fraction=null&world_width=%32%30%30")
+            .unwrap();
+        assert_eq!(config.rng_seed(), 7);
+        assert_eq!(config.metabolism.basal_rate, 0.5);
+        assert_eq!(config.graphics.creature_coloring, CreatureColoring::Species);
+        assert_eq!(config.creature_carcass_energy_fraction(), None);
+        assert_eq!(config.world_width(), 200.);
+        assert_eq!(config.world_height(), Config::default().world_height());
+    }
+
+    #[test]
+    pub fn set_rejects_bad_keys_and_values() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.set("metabolism.nonexistent", "1"),
+            Err(ConfigError::UnknownKey(
+                "metabolism.nonexistent".to_string()
+            ))
+        );
+        assert!(matches!(
+            config.set("world_width", "wide"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        // Values that parse but would break the simulation are rejected and leave the config unchanged.
+        for query in [
+            "tick_length=0",
+            "world_width=-5",
+            "drag=-1",
+            "reproduction.litter_size=1",
+            "terrain.cell_size=0",
+            "terrain.obstacle_fraction=1.5",
+            "aging.starting_lifespan=0",
+            "aging.maturity_age=-1",
+            "aging.mortality={\"Gompertz\":{\"base_rate\":0,\"doubling_time\":60}}",
+            "physics.sub_steps=0",
+            "graphics.histogram_bins=0",
+            "graphics.heatmap_cell_size=0",
+        ] {
+            assert!(
+                matches!(config.apply_query(query), Err(ConfigError::OutOfRange(_))),
+                "{query}"
+            );
+        }
+        assert_eq!(config.tick_length(), Config::default().tick_length());
+        assert_eq!(config.world_width(), Config::default().world_width());
+        // Only the final config has to be valid, so fields that constrain each other can be set in any order.
+        config
+            .apply_query("reproduction.litter_size=1&reproduction.mode=Budding")
+            .unwrap();
+        assert_eq!(config.reproduction.litter_size, 1);
+        assert_eq!(
+            config.apply_query("seed"),
+            Err(ConfigError::MalformedQuery("seed".to_string()))
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// What happens to a creature when it reproduces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ReproductionMode {
+    /// The creature splits all its energy between its children and ceases to exist.
+    #[default]
+    Fission,
+    /// The creature survives, giving a fraction of its energy to its children.
+    Budding,
+}
+
+/// Parameters of reproduction. Creatures reproduce once they have more than the reproduction energy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Reproduction {
+    pub mode: ReproductionMode,
+    /// Number of children per reproduction.
+    pub litter_size: u32,
+    /// Fraction of its energy a budding creature gives to its children.
+    pub investment: f32,
+    /// Energy lost every time a creature reproduces.
+    pub cost: f32,
+}
+
+impl Default for Reproduction {
+    fn default() -> Self {
+        Self {
+            mode: ReproductionMode::default(),
+            litter_size: 2,
+            investment: 0.5,
+            cost: 0.,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// Parameters of the ground the world is made of, see [`crate::world::TerrainGrid`].
+///
+/// Cells are mud, water or obstacles with the given fractions and open ground otherwise. Open ground has the drag of
+/// the world. The default is open ground everywhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Terrain {
+    /// Width and height of terrain cells in world units.
+    pub cell_size: f32,
+    pub mud_fraction: f32,
+    pub water_fraction: f32,
+    /// Fraction of impassable cells.
+    pub obstacle_fraction: f32,
+    /// Number of smoothing passes that merge scattered cells into patches. Each pass grows the most common ground at
+    /// the expense of the others.
+    pub smoothing: u32,
+    pub mud_drag: f32,
+    pub water_drag: f32,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Self {
+            cell_size: 5.,
+            mud_fraction: 0.,
+            water_fraction: 0.,
+            obstacle_fraction: 0.,
+            smoothing: 2,
+            mud_drag: 2.,
+            water_drag: 0.5,
+        }
+    }
+}
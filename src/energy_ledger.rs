@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+
+use crate::world::MetabolicCost;
+
+/// Relative tolerance used when checking that the books balance.
+const BALANCE_TOLERANCE: f64 = 1e-6;
+
+/// Accounts for all energy entering, leaving and moving around the world during a single tick.
+///
+/// The energy in the world is the energy stored in creatures plus the energy held by food.
+/// Amounts are accumulated as `f64` so rounding does not drown out small flows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnergyLedger {
+    opening_balance: f64,
+    closing_balance: f64,
+    food_spawned: f64,
+    carcasses: f64,
+    food_eaten: f64,
+    basal_metabolism: f64,
+    movement: f64,
+    sensing: f64,
+    thermoregulation: f64,
+    digestion: f64,
+    deaths: f64,
+    reproduction: f64,
+}
+
+impl EnergyLedger {
+    /// Opens a new ledger for a tick starting with `opening_balance` energy in the world.
+    pub fn new(opening_balance: f64) -> Self {
+        Self {
+            opening_balance,
+            closing_balance: opening_balance,
+            ..Default::default()
+        }
+    }
+
+    /// Energy in the world at the start of the tick.
+    pub fn opening_balance(&self) -> f64 {
+        self.opening_balance
+    }
+
+    /// Energy in the world at the end of the tick.
+    pub fn closing_balance(&self) -> f64 {
+        self.closing_balance
+    }
+
+    /// Energy added to the world as newly spawned food.
+    pub fn food_spawned(&self) -> f64 {
+        self.food_spawned
+    }
+
+    /// Energy added to the world by the bodies of dead creatures turning into carcasses.
+    pub fn carcasses(&self) -> f64 {
+        self.carcasses
+    }
+
+    /// Energy moved from food to creatures by eating, before any digestion losses.
+    pub fn food_eaten(&self) -> f64 {
+        self.food_eaten
+    }
+
+    /// Energy spent by creatures on metabolism.
+    pub fn metabolism(&self) -> f64 {
+        self.basal_metabolism + self.movement + self.sensing + self.thermoregulation
+    }
+
+    /// Energy spent by creatures on staying alive.
+    pub fn basal_metabolism(&self) -> f64 {
+        self.basal_metabolism
+    }
+
+    /// Energy spent by creatures on moving.
+    pub fn movement(&self) -> f64 {
+        self.movement
+    }
+
+    /// Energy spent by creatures on sensing.
+    pub fn sensing(&self) -> f64 {
+        self.sensing
+    }
+
+    /// Energy spent by creatures on coping with the temperature.
+    pub fn thermoregulation(&self) -> f64 {
+        self.thermoregulation
+    }
+
+    /// Energy in eaten food that creatures could not absorb because they were saturated.
+    pub fn digestion(&self) -> f64 {
+        self.digestion
+    }
+
+    /// Energy stored in creatures when they died.
+    pub fn deaths(&self) -> f64 {
+        self.deaths
+    }
+
+    /// Energy lost when creatures reproduced.
+    pub fn reproduction(&self) -> f64 {
+        self.reproduction
+    }
+
+    /// Total energy entering the world.
+    pub fn inflow(&self) -> f64 {
+        self.food_spawned + self.carcasses
+    }
+
+    /// Total energy spent by creatures.
+    pub fn outflow(&self) -> f64 {
+        self.metabolism()
+    }
+
+    /// Total energy lost to inefficiencies.
+    pub fn losses(&self) -> f64 {
+        self.digestion + self.deaths + self.reproduction
+    }
+
+    /// The difference between the closing balance and what the recorded flows account for.
+    pub fn imbalance(&self) -> f64 {
+        self.closing_balance
+            - (self.opening_balance + self.inflow() - self.outflow() - self.losses())
+    }
+
+    /// Whether the recorded flows account for the change in energy up to rounding errors.
+    pub fn is_balanced(&self) -> bool {
+        self.imbalance().abs()
+            <= BALANCE_TOLERANCE * (1. + self.opening_balance.abs() + self.closing_balance.abs())
+    }
+
+    pub(crate) fn record_food_spawned(&mut self, energy: f32) {
+        self.food_spawned += energy as f64;
+    }
+
+    pub(crate) fn record_carcass(&mut self, energy: f32) {
+        self.carcasses += energy as f64;
+    }
+
+    pub(crate) fn record_metabolism(&mut self, cost: MetabolicCost) {
+        self.basal_metabolism += cost.basal() as f64;
+        self.movement += cost.movement() as f64;
+        self.sensing += cost.sensing() as f64;
+        self.thermoregulation += cost.thermoregulation() as f64;
+    }
+
+    /// Records a creature eating `eaten` energy worth of food and failing to absorb `overflow` of it.
+    pub(crate) fn record_eating(&mut self, eaten: f32, overflow: f32) {
+        self.food_eaten += eaten as f64;
+        self.digestion += overflow as f64;
+    }
+
+    pub(crate) fn record_death(&mut self, stored_energy: f32) {
+        self.deaths += stored_energy as f64;
+    }
+
+    pub(crate) fn record_reproduction(&mut self, cost: f32) {
+        self.reproduction += cost as f64;
+    }
+
+    pub(crate) fn close(&mut self, closing_balance: f64) {
+        self.closing_balance = closing_balance;
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::world::{EntityId, Location};
+
+/// Why a creature died.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum DeathCause {
+    /// The creature ran out of energy.
+    Starvation,
+    #[strum(to_string = "old age")]
+    OldAge,
+}
+
+/// Something notable that happened during a tick.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    /// A creature was born from `parent`, which no longer exists unless it reproduced by budding.
+    Birth {
+        id: EntityId,
+        parent: EntityId,
+        location: Location,
+    },
+    /// A creature died, possibly leaving a carcass behind.
+    Death {
+        id: EntityId,
+        location: Location,
+        cause: DeathCause,
+        carcass: Option<EntityId>,
+    },
+    /// A creature ate a piece of food.
+    Eaten {
+        food: EntityId,
+        creature: EntityId,
+        location: Location,
+    },
+}
+
+impl Event {
+    /// Where the event happened.
+    pub fn location(&self) -> Location {
+        match self {
+            Event::Birth { location, .. }
+            | Event::Death { location, .. }
+            | Event::Eaten { location, .. } => *location,
+        }
+    }
+}
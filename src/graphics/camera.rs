@@ -16,6 +16,16 @@ impl Camera {
         Self { location, zoom }
     }
 
+    pub fn location(&self) -> Location {
+        self.location
+    }
+
+    /// The number of screen pixels per world unit.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Returns a camera fitting the whole world into a view of the given size.
     pub fn view_whole_world(config: &Config, screen_size: Vector) -> Self {
         let min_world_screen_ratio =
             (screen_size.x / config.world_width()).min(screen_size.y / config.world_height());
@@ -41,4 +51,49 @@ impl Camera {
     pub fn camera_to_world(&self, location: Location) -> Location {
         self.location + (location - Location::ORIGIN) / self.zoom
     }
+
+    /// Moves the camera so the world follows a movement of `screen_delta` pixels on the screen.
+    pub fn pan(&mut self, screen_delta: Vector) {
+        self.location -= screen_delta / self.zoom;
+    }
+
+    /// Multiplies the zoom by `factor` while keeping the world location under `screen_location` in place.
+    pub fn zoom_around(&mut self, screen_location: Location, factor: f32) {
+        let world_location = self.camera_to_world(screen_location);
+        self.zoom *= factor;
+        self.location = world_location - (screen_location - Location::ORIGIN) / self.zoom;
+    }
+
+    /// Moves the camera so `world_location` is in the center of a view of the given size.
+    pub fn center_on(&mut self, world_location: Location, view_size: Vector) {
+        self.location = world_location - view_size / (2. * self.zoom);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn zoom_around_keeps_point_fixed() {
+        let mut camera = Camera::new(Location::new(10., 20.), 4.);
+        let screen_location = Location::new(120., 80.);
+        let world_location = camera.camera_to_world(screen_location);
+
+        camera.zoom_around(screen_location, 2.5);
+        let new_screen_location = camera.world_to_camera(world_location);
+        assert!((new_screen_location - screen_location).norm() < 1e-3);
+        assert_eq!(camera.zoom(), 10.);
+    }
+
+    #[test]
+    pub fn center_on_centers() {
+        let mut camera = Camera::new(Location::ORIGIN, 2.);
+        let view_size = Vector::new(400., 300.);
+        camera.center_on(Location::new(50., 50.), view_size);
+        assert_eq!(
+            camera.world_to_camera(Location::new(50., 50.)),
+            Location::new(200., 150.)
+        );
+    }
 }
@@ -0,0 +1,188 @@
+use macroquad::{
+    color::Color,
+    math::Rect,
+    shapes,
+    text::{self, TextParams},
+};
+
+use crate::{config::Graphics, Histogram};
+
+use super::vec_to_color;
+
+/// A line in a line chart.
+pub struct Series<'a> {
+    pub label: &'a str,
+    pub values: &'a [f32],
+    pub color: Color,
+}
+
+fn draw_text(text: &str, x: f32, y: f32, font_size: u16, color: Color) {
+    text::draw_text_ex(
+        text,
+        x,
+        y,
+        TextParams {
+            font_size,
+            color,
+            ..Default::default()
+        },
+    );
+}
+
+/// Draws the chart background and title and returns the area left for the plot.
+fn draw_frame(rect: Rect, title: &str, graphics: &Graphics) -> Rect {
+    let (font_size, margin) = (graphics.small_font_size, graphics.chart_margin);
+    shapes::draw_rectangle(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        vec_to_color(graphics.chart_background_color),
+    );
+    shapes::draw_rectangle_lines(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        1.,
+        vec_to_color(graphics.chart_border_color),
+    );
+    draw_text(
+        title,
+        rect.x + margin,
+        rect.y + font_size as f32,
+        font_size,
+        vec_to_color(graphics.text_color),
+    );
+    let top = font_size as f32 + 2. * margin;
+    let bottom = font_size as f32 + margin;
+    Rect::new(
+        rect.x + margin,
+        rect.y + top,
+        rect.w - 2. * margin,
+        rect.h - top - bottom,
+    )
+}
+
+/// Draws the series as lines over the full width of the chart.
+///
+/// If `from_zero` is set the y-axis always includes zero, otherwise it spans the range of the values.
+pub fn draw_line_chart(
+    rect: Rect,
+    title: &str,
+    series: &[Series],
+    from_zero: bool,
+    graphics: &Graphics,
+) {
+    let plot = draw_frame(rect, title, graphics);
+    let (font_size, margin) = (graphics.small_font_size, graphics.chart_margin);
+    let label_color = vec_to_color(graphics.chart_label_color);
+
+    let finite_values = || {
+        series
+            .iter()
+            .flat_map(|series| series.values.iter().copied())
+            .filter(|value| value.is_finite())
+    };
+    let mut min = finite_values().fold(f32::INFINITY, f32::min);
+    let mut max = finite_values().fold(f32::NEG_INFINITY, f32::max);
+    if min > max {
+        // No values to draw.
+        return;
+    }
+    if from_zero {
+        min = min.min(0.);
+        max = max.max(0.);
+    }
+    if max - min < f32::EPSILON * max.abs().max(1.) {
+        min -= 0.5;
+        max += 0.5;
+    }
+    let to_y = |value: f32| plot.y + plot.h * (1. - (value - min) / (max - min));
+
+    for series in series {
+        let step = plot.w / (series.values.len().max(2) - 1) as f32;
+        for (index, window) in series.values.windows(2).enumerate() {
+            if window[0].is_finite() && window[1].is_finite() {
+                shapes::draw_line(
+                    plot.x + index as f32 * step,
+                    to_y(window[0]),
+                    plot.x + (index + 1) as f32 * step,
+                    to_y(window[1]),
+                    1.5,
+                    series.color,
+                );
+            }
+        }
+    }
+
+    // Axis labels and legend.
+    draw_text(
+        &format!("{max:.2}"),
+        plot.x,
+        plot.y + font_size as f32 * 0.7,
+        font_size,
+        label_color,
+    );
+    let bottom = rect.y + rect.h - margin;
+    draw_text(&format!("{min:.2}"), plot.x, bottom, font_size, label_color);
+    let mut legend_x = plot.x + plot.w;
+    for series in series.iter().rev() {
+        let latest = series.values.last().copied().unwrap_or(f32::NAN);
+        let label = format!("{}: {latest:.2}", series.label);
+        legend_x -= text::measure_text(&label, None, font_size, 1.).width + 2. * margin;
+        draw_text(&label, legend_x, bottom, font_size, series.color);
+    }
+}
+
+/// Draws the histogram as bars over the full width of the chart.
+pub fn draw_histogram(
+    rect: Rect,
+    title: &str,
+    histogram: &Histogram,
+    color: Color,
+    graphics: &Graphics,
+) {
+    let plot = draw_frame(rect, title, graphics);
+    let (font_size, margin) = (graphics.small_font_size, graphics.chart_margin);
+    let label_color = vec_to_color(graphics.chart_label_color);
+
+    let counts = histogram.counts();
+    let max_count = counts.iter().copied().max().unwrap_or(0).max(1);
+    let bar_width = plot.w / counts.len() as f32;
+    for (index, &count) in counts.iter().enumerate() {
+        let height = plot.h * count as f32 / max_count as f32;
+        shapes::draw_rectangle(
+            plot.x + index as f32 * bar_width + 0.5,
+            plot.y + plot.h - height,
+            (bar_width - 1.).max(1.),
+            height,
+            color,
+        );
+    }
+
+    let bottom = rect.y + rect.h - margin;
+    draw_text(
+        &format!("{:.2}", histogram.min()),
+        plot.x,
+        bottom,
+        font_size,
+        label_color,
+    );
+    let max_label = format!("{:.2}", histogram.max());
+    let max_label_width = text::measure_text(&max_label, None, font_size, 1.).width;
+    draw_text(
+        &max_label,
+        plot.x + plot.w - max_label_width,
+        bottom,
+        font_size,
+        label_color,
+    );
+    draw_text(
+        &format!("max: {max_count}"),
+        plot.x + plot.w * 0.5 - 20.,
+        bottom,
+        font_size,
+        label_color,
+    );
+}
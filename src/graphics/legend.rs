@@ -0,0 +1,78 @@
+use macroquad::{
+    shapes,
+    text::{self, TextParams},
+};
+
+use crate::{coloring::Legend, config::Graphics};
+
+use super::vec_to_color;
+
+/// Draws the legend in screen coordinates with its upper left corner at `(x, y)`.
+pub fn draw_legend(legend: &Legend, x: f32, y: f32, graphics: &Graphics) {
+    let font_size = graphics.small_font_size;
+    let margin = graphics.chart_margin;
+    let width = graphics.legend_width;
+    let swatch_size = graphics.legend_swatch_size;
+    let background = vec_to_color(graphics.legend_background_color);
+    let draw_text = |text: &str, x: f32, y: f32| {
+        text::draw_text_ex(
+            text,
+            x,
+            y,
+            TextParams {
+                font_size,
+                color: vec_to_color(graphics.text_color),
+                ..Default::default()
+            },
+        );
+    };
+    let line_height = font_size as f32 * 1.15;
+    match legend {
+        Legend::Gradient {
+            label,
+            min,
+            max,
+            colors,
+        } => {
+            let height = 2. * line_height + swatch_size + 2. * margin;
+            shapes::draw_rectangle(x, y, width, height, background);
+            draw_text(label, x + margin, y + line_height);
+            let bar_y = y + line_height + margin;
+            let bar_width = (width - 2. * margin) / colors.len() as f32;
+            for (index, &color) in colors.iter().enumerate() {
+                shapes::draw_rectangle(
+                    x + margin + index as f32 * bar_width,
+                    bar_y,
+                    bar_width,
+                    swatch_size,
+                    vec_to_color(color),
+                );
+            }
+            let labels_y = bar_y + swatch_size + line_height;
+            draw_text(&format!("{min:.2}"), x + margin, labels_y);
+            let max_label = format!("{max:.2}");
+            let max_label_width = text::measure_text(&max_label, None, font_size, 1.).width;
+            draw_text(&max_label, x + width - margin - max_label_width, labels_y);
+        }
+        Legend::Categories { label, entries } => {
+            let height = (entries.len() + 1) as f32 * line_height + 2. * margin;
+            shapes::draw_rectangle(x, y, width, height, background);
+            draw_text(label, x + margin, y + line_height);
+            for (index, entry) in entries.iter().enumerate() {
+                let line_y = y + (index + 2) as f32 * line_height;
+                let radius = 0.5 * swatch_size;
+                shapes::draw_circle(
+                    x + margin + radius,
+                    line_y - radius,
+                    radius,
+                    vec_to_color(entry.color),
+                );
+                draw_text(
+                    &format!("{} ({})", entry.label, entry.count),
+                    x + 2. * margin + swatch_size,
+                    line_y,
+                );
+            }
+        }
+    }
+}
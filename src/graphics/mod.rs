@@ -1,7 +1,12 @@
 mod camera;
 pub use camera::Camera;
+pub mod charts;
+mod legend;
+pub use legend::draw_legend;
+mod overlays;
 use macroquad::{color::Color, window};
 use nalgebra::Vector4;
+pub use overlays::{draw_heatmap, draw_overlays, draw_terrain, Trails};
 
 use crate::world::Vector;
 
@@ -0,0 +1,247 @@
+use std::collections::{HashMap, VecDeque};
+
+use macroquad::{color::Color, shapes};
+
+use crate::{
+    config::Graphics,
+    world::{Entity, EntityData, EntityId, Ground, Location, TerrainGrid, Vector},
+    Config, Heatmap, State,
+};
+
+use super::vec_to_color;
+
+/// Number of line segments used for a full circle.
+const CIRCLE_SEGMENTS: usize = 48;
+
+/// Recent positions of every creature.
+#[derive(Debug, Default)]
+pub struct Trails {
+    trails: HashMap<EntityId, VecDeque<Location>>,
+    /// The tick the last locations were recorded at.
+    last_tick: Option<u64>,
+}
+
+impl Trails {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the current location of every creature, keeping at most `length` locations per creature.
+    ///
+    /// Trails of creatures that no longer exist are dropped. Does nothing if the state has not ticked since the last call.
+    pub fn record(&mut self, state: &State, length: usize) {
+        if self.last_tick == Some(state.tick_count()) {
+            return;
+        }
+        self.last_tick = Some(state.tick_count());
+        if length == 0 {
+            self.trails.clear();
+            return;
+        }
+        let mut trails = HashMap::with_capacity(self.trails.len());
+        for entity in state.entities().filter(|entity| entity.is_creature()) {
+            let mut trail = self.trails.remove(&entity.id()).unwrap_or_default();
+            if trail.len() >= length {
+                trail.drain(..=trail.len() - length);
+            }
+            trail.push_back(entity.location());
+            trails.insert(entity.id(), trail);
+        }
+        self.trails = trails;
+    }
+
+    pub fn clear(&mut self) {
+        self.trails.clear();
+        self.last_tick = None;
+    }
+
+    /// Draws the trails in world coordinates, fading out towards the oldest location.
+    pub fn draw(&self, graphics: &Graphics, thickness: f32) {
+        for trail in self.trails.values() {
+            let len = trail.len();
+            for (index, (from, to)) in trail.iter().zip(trail.iter().skip(1)).enumerate() {
+                let mut color = graphics.trail_color;
+                color.w *= (index + 1) as f32 / len as f32;
+                shapes::draw_line(
+                    from.x(),
+                    from.y(),
+                    to.x(),
+                    to.y(),
+                    thickness,
+                    vec_to_color(color),
+                );
+            }
+        }
+    }
+}
+
+/// Draws the ground of every cell that is not open ground in world coordinates, clipped to the world.
+pub fn draw_terrain(terrain: &TerrainGrid, config: &Config, graphics: &Graphics) {
+    let cell_size = terrain.cell_size();
+    for row in 0..terrain.rows() {
+        for column in 0..terrain.columns() {
+            let ground = terrain.ground(column, row);
+            if ground != Ground::Open {
+                let (x, y) = (column as f32 * cell_size, row as f32 * cell_size);
+                shapes::draw_rectangle(
+                    x,
+                    y,
+                    cell_size.min(config.world_width() - x),
+                    cell_size.min(config.world_height() - y),
+                    vec_to_color(graphics.ground_color(ground)),
+                );
+            }
+        }
+    }
+}
+
+/// Draws the heatmap cells in world coordinates.
+pub fn draw_heatmap(heatmap: &Heatmap, graphics: &Graphics) {
+    let cell_size = heatmap.cell_size();
+    for row in 0..heatmap.rows() {
+        for column in 0..heatmap.columns() {
+            if let Some(color) = heatmap.color(column, row, graphics.heatmap_opacity) {
+                shapes::draw_rectangle(
+                    column as f32 * cell_size,
+                    row as f32 * cell_size,
+                    cell_size,
+                    cell_size,
+                    vec_to_color(color),
+                );
+            }
+        }
+    }
+}
+
+fn draw_arrow(from: Location, delta: Vector, thickness: f32, color: Color) {
+    let to = from + delta;
+    shapes::draw_line(from.x(), from.y(), to.x(), to.y(), thickness, color);
+    let length = delta.norm();
+    if length > 0. {
+        let head = delta / length * (length * 0.3).min(6. * thickness);
+        for side in [-1., 1.] {
+            let barb = to - nalgebra::Rotation2::new(side * 0.5).transform_vector(&head);
+            shapes::draw_line(to.x(), to.y(), barb.x(), barb.y(), thickness, color);
+        }
+    }
+}
+
+/// Draws the outline of the area a creature facing `heading` can sense.
+fn draw_sensing_area(
+    location: Location,
+    range: f32,
+    field_of_view: f32,
+    heading: f32,
+    thickness: f32,
+    color: Color,
+) {
+    let full_circle = field_of_view >= std::f32::consts::TAU;
+    let segments =
+        ((CIRCLE_SEGMENTS as f32 * field_of_view / std::f32::consts::TAU).ceil() as usize).max(1);
+    let start = heading - field_of_view * 0.5;
+    let point = |angle: f32| location + Vector::new(angle.cos(), angle.sin()) * range;
+    let arc: Vec<_> = (0..=segments)
+        .map(|index| point(start + field_of_view * index as f32 / segments as f32))
+        .collect();
+    for (from, to) in arc.iter().zip(arc.iter().skip(1)) {
+        shapes::draw_line(from.x(), from.y(), to.x(), to.y(), thickness, color);
+    }
+    if !full_circle {
+        for edge in [arc[0], arc[segments]] {
+            shapes::draw_line(
+                location.x(),
+                location.y(),
+                edge.x(),
+                edge.y(),
+                thickness,
+                color,
+            );
+        }
+    }
+}
+
+/// Draws the enabled per-creature overlays in world coordinates.
+///
+/// `pixel` is the size of a screen pixel in world units. Entities are drawn at the location `location` gives for them, so
+/// overlays can follow interpolated locations.
+pub fn draw_overlays(
+    state: &State,
+    graphics: &Graphics,
+    pixel: f32,
+    location: impl Fn(&Entity) -> Location,
+) {
+    let thickness = 1.5 * pixel;
+    let food_locations: HashMap<_, _> = if graphics.show_targets {
+        state
+            .entities()
+            .filter(|entity| entity.is_food())
+            .map(|entity| (entity.id(), location(entity)))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    for entity in state.entities() {
+        let EntityData::Creature(creature) = entity.entity_data() else {
+            continue;
+        };
+        let location = location(entity);
+        if graphics.show_sensing {
+            draw_sensing_area(
+                location,
+                creature.sensing_range(),
+                creature.field_of_view(),
+                creature.heading(),
+                thickness,
+                vec_to_color(graphics.sensing_color),
+            );
+        }
+        if graphics.show_targets {
+            if let Some(target) = creature
+                .target()
+                .and_then(|target| food_locations.get(&target))
+            {
+                shapes::draw_line(
+                    location.x(),
+                    location.y(),
+                    target.x(),
+                    target.y(),
+                    thickness,
+                    vec_to_color(graphics.target_color),
+                );
+            }
+        }
+        if graphics.show_velocities {
+            draw_arrow(
+                location,
+                entity.velocity() * graphics.velocity_scale,
+                thickness,
+                vec_to_color(graphics.velocity_color),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn trails_keep_at_most_trail_length_locations() {
+        let mut state = State::init(Config::default(), 3);
+        let mut trails = Trails::new();
+        for _ in 0..10 {
+            state.tick();
+            trails.record(&state, 4);
+            assert!(trails.trails.values().all(|trail| trail.len() <= 4));
+        }
+        assert!(trails.trails.values().all(|trail| trail.len() == 4));
+
+        state.tick();
+        trails.record(&state, 0);
+        assert!(trails.trails.is_empty());
+        state.tick();
+        trails.record(&state, 0);
+        assert!(trails.trails.is_empty());
+    }
+}
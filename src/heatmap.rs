@@ -0,0 +1,171 @@
+use nalgebra::Vector4;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+use crate::{coloring, world::Location, Config, Event, State};
+
+/// What a heatmap accumulates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter, Display)]
+pub enum HeatmapLayer {
+    /// Seconds creatures spent in each cell.
+    #[default]
+    Creatures,
+    /// Seconds food spent in each cell.
+    Food,
+    /// Number of creatures born in each cell.
+    Births,
+    /// Number of creatures that died in each cell.
+    Deaths,
+}
+
+/// A grid over the world accumulating where things are or happen, with older values decaying exponentially.
+#[derive(Debug, Clone)]
+pub struct Heatmap {
+    layer: HeatmapLayer,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    /// Row-major cell values.
+    values: Vec<f32>,
+    /// The largest cell value, updated whenever the values change.
+    max: f32,
+    /// Seconds after which a value has decayed to half.
+    half_life: f32,
+    /// The tick the last values were recorded at.
+    last_tick: Option<u64>,
+}
+
+impl Heatmap {
+    pub fn new(config: &Config, layer: HeatmapLayer, cell_size: f32, half_life: f32) -> Self {
+        let columns = ((config.world_width() / cell_size).ceil() as usize).max(1);
+        let rows = ((config.world_height() / cell_size).ceil() as usize).max(1);
+        Self {
+            layer,
+            cell_size,
+            columns,
+            rows,
+            values: vec![0.; columns * rows],
+            max: 0.,
+            half_life,
+            last_tick: None,
+        }
+    }
+
+    pub fn layer(&self) -> HeatmapLayer {
+        self.layer
+    }
+
+    /// The width and height of a cell in world units.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The value of the cell in the given column and row.
+    pub fn value(&self, column: usize, row: usize) -> f32 {
+        self.values[row * self.columns + column]
+    }
+
+    /// The largest cell value.
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    /// The color of the cell on a gradient up to the hottest cell, fading to transparent for cold cells.
+    ///
+    /// `None` for empty cells. `opacity` is the opacity of the hottest cell.
+    pub fn color(&self, column: usize, row: usize, opacity: f32) -> Option<Vector4<f32>> {
+        let max = self.max;
+        let t = self.value(column, row) / max;
+        (max > 0. && t > 0.).then(|| {
+            let mut color = coloring::gradient_color(t);
+            color.w = opacity * t.sqrt();
+            color
+        })
+    }
+
+    /// Decays the heatmap by one tick and adds the current state.
+    ///
+    /// Call once per tick so no births or deaths are missed. Does nothing if the state has not ticked since the last call.
+    pub fn record(&mut self, state: &State) {
+        if self.last_tick == Some(state.tick_count()) {
+            return;
+        }
+        self.last_tick = Some(state.tick_count());
+        let tick_length = state.config().tick_length();
+        let decay = 0.5f32.powf(tick_length / self.half_life);
+        for value in self.values.iter_mut() {
+            *value *= decay;
+        }
+        match self.layer {
+            HeatmapLayer::Creatures | HeatmapLayer::Food => {
+                let is_creature = self.layer == HeatmapLayer::Creatures;
+                let locations: Vec<_> = state
+                    .entities()
+                    .filter(|entity| entity.is_creature() == is_creature)
+                    .map(|entity| entity.location())
+                    .collect();
+                for location in locations {
+                    self.add(location, tick_length);
+                }
+            }
+            HeatmapLayer::Births | HeatmapLayer::Deaths => {
+                for event in state.events() {
+                    if matches!(
+                        (self.layer, event),
+                        (HeatmapLayer::Births, Event::Birth { .. })
+                            | (HeatmapLayer::Deaths, Event::Death { .. })
+                    ) {
+                        self.add(event.location(), 1.);
+                    }
+                }
+            }
+        }
+        self.max = self.values.iter().copied().fold(0., f32::max);
+    }
+
+    pub fn clear(&mut self) {
+        self.values.fill(0.);
+        self.max = 0.;
+        self.last_tick = None;
+    }
+
+    fn add(&mut self, location: Location, amount: f32) {
+        let column = ((location.x() / self.cell_size).max(0.) as usize).min(self.columns - 1);
+        let row = ((location.y() / self.cell_size).max(0.) as usize).min(self.rows - 1);
+        self.values[row * self.columns + column] += amount;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn heatmap_decays_by_half_life() {
+        let config = Config::default();
+        let mut state = State::init(config.clone(), 1);
+        let half_life = 10. * config.tick_length();
+        let mut heatmap = Heatmap::new(&config, HeatmapLayer::Creatures, 5., half_life);
+        heatmap.record(&state);
+        let total = heatmap.values.iter().sum::<f32>();
+        assert!((total - config.tick_length()).abs() < 1e-6);
+        heatmap.layer = HeatmapLayer::Deaths;
+        for _ in 0..10 {
+            state.tick();
+            heatmap.record(&state);
+        }
+        let total = heatmap.values.iter().sum::<f32>();
+        assert!((total - 0.5 * config.tick_length()).abs() < 1e-6);
+        assert_eq!(heatmap.max(), total);
+        heatmap.clear();
+        assert_eq!(heatmap.max(), 0.);
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    world::{CreatureTrait, EntityData},
+    State,
+};
+
+/// Summary statistics of the world at a single tick.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    tick: u64,
+    creatures: usize,
+    food: usize,
+    /// Mean of each trait over all creatures, indexed by [`CreatureTrait::index`].
+    trait_means: Vec<f32>,
+}
+
+impl Sample {
+    pub fn new(state: &State) -> Self {
+        let mut creatures = 0;
+        let mut food = 0;
+        let mut trait_sums = vec![0.; CreatureTrait::iter().len()];
+        for entity in state.entities() {
+            match entity.entity_data() {
+                EntityData::Creature(creature) => {
+                    creatures += 1;
+                    for creature_trait in CreatureTrait::iter() {
+                        trait_sums[creature_trait.index()] += creature.trait_value(creature_trait);
+                    }
+                }
+                EntityData::Food(_) => food += 1,
+            }
+        }
+        let trait_means = trait_sums
+            .into_iter()
+            .map(|sum| sum / creatures as f32)
+            .collect();
+
+        Self {
+            tick: state.tick_count(),
+            creatures,
+            food,
+            trait_means,
+        }
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn creatures(&self) -> usize {
+        self.creatures
+    }
+
+    pub fn food(&self) -> usize {
+        self.food
+    }
+
+    /// The mean of the trait over all creatures. `NaN` if there were no creatures.
+    pub fn trait_mean(&self, creature_trait: CreatureTrait) -> f32 {
+        self.trait_means[creature_trait.index()]
+    }
+}
+
+/// A rolling buffer of samples taken at regular intervals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct History {
+    /// Number of ticks between samples.
+    interval: u64,
+    capacity: usize,
+    samples: VecDeque<Sample>,
+}
+
+impl History {
+    pub fn new(interval: u64, capacity: usize) -> Self {
+        assert!(interval > 0, "History interval must be positive.");
+        Self {
+            interval,
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Number of ticks between samples.
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The stored samples from oldest to newest.
+    pub fn samples(&self) -> impl ExactSizeIterator<Item = &Sample> + DoubleEndedIterator {
+        self.samples.iter()
+    }
+
+    pub fn latest(&self) -> Option<&Sample> {
+        self.samples.back()
+    }
+
+    /// Whether a sample should be taken at the given tick.
+    pub fn is_due(&self, tick: u64) -> bool {
+        tick.is_multiple_of(self.interval)
+    }
+
+    /// Adds a sample, dropping the oldest sample if the buffer is full.
+    pub fn push(&mut self, sample: Sample) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+/// Counts of values falling into equally wide bins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Histogram {
+    min: f32,
+    max: f32,
+    counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Bins the values into `num_bins` bins spanning the range of the values.
+    pub fn new(values: impl IntoIterator<Item = f32>, num_bins: usize) -> Self {
+        assert!(num_bins > 0, "A histogram needs at least one bin.");
+        let values: Vec<_> = values
+            .into_iter()
+            .filter(|value| value.is_finite())
+            .collect();
+        let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let mut counts = vec![0; num_bins];
+        if values.is_empty() {
+            return Self {
+                min: 0.,
+                max: 0.,
+                counts,
+            };
+        }
+        let width = (max - min) / num_bins as f32;
+        for value in values {
+            let bin = if width > 0. {
+                (((value - min) / width) as usize).min(num_bins - 1)
+            } else {
+                0
+            };
+            counts[bin] += 1;
+        }
+        Self { min, max, counts }
+    }
+
+    /// Histogram of a trait over all creatures in the state.
+    pub fn of_trait(state: &State, creature_trait: CreatureTrait, num_bins: usize) -> Self {
+        Self::new(
+            state
+                .entities()
+                .filter_map(|entity| match entity.entity_data() {
+                    EntityData::Creature(creature) => Some(creature.trait_value(creature_trait)),
+                    _ => None,
+                }),
+            num_bins,
+        )
+    }
+
+    /// The smallest binned value.
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    /// The largest binned value.
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn histogram_bins() {
+        let histogram = Histogram::new([0., 1., 1.5, 2., 3.9, 4.], 4);
+        assert_eq!(histogram.min(), 0.);
+        assert_eq!(histogram.max(), 4.);
+        assert_eq!(histogram.counts(), &[1, 2, 1, 2]);
+    }
+
+    #[test]
+    pub fn histogram_of_equal_values() {
+        let histogram = Histogram::new([2., 2., 2.], 3);
+        assert_eq!(histogram.counts(), &[3, 0, 0]);
+    }
+}
@@ -1,8 +1,24 @@
 mod entity_list;
 pub use entity_list::EntityList;
-mod config;
+pub mod config;
 pub mod world;
-pub use config::Config;
+pub use config::{Config, CreatureColoring};
+pub mod coloring;
+mod energy_ledger;
+pub use energy_ledger::EnergyLedger;
+mod event;
+pub use event::{DeathCause, Event};
+mod heatmap;
+pub use heatmap::{Heatmap, HeatmapLayer};
+mod history;
+pub use history::{Histogram, History, Sample};
+pub mod render;
+pub mod replay;
+pub mod runner;
+mod scheduler;
+pub use scheduler::{FrameTicks, Scheduler};
 mod state;
 pub use state::State;
+#[cfg(feature = "gui")]
 pub mod graphics;
+pub mod sweep;
@@ -0,0 +1,203 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use nalgebra::Vector4;
+
+use crate::{
+    coloring::ColorScheme,
+    config::Graphics,
+    world::{EntityData, Ground, Location},
+    Heatmap, State,
+};
+
+/// An RGBA image drawn in software, used to render states without opening a window.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    /// Row-major pixels with components in `[0, 1]`.
+    pixels: Vec<Vector4<f32>>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, color: Vector4<f32>) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The color of the pixel in column `x` and row `y`.
+    pub fn pixel(&self, x: u32, y: u32) -> Vector4<f32> {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Blends `color` over the pixel by its alpha times `coverage`. Pixels outside the canvas are ignored.
+    fn blend(&mut self, x: i64, y: i64, color: Vector4<f32>, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let alpha = color.w * coverage;
+        let pixel = &mut self.pixels[y as usize * self.width as usize + x as usize];
+        let rgb = color.xyz() * alpha + pixel.xyz() * (1. - alpha);
+        *pixel = Vector4::new(rgb.x, rgb.y, rgb.z, alpha + pixel.w * (1. - alpha));
+    }
+
+    /// Fills the rectangle with its upper left at `(x, y)`, rounding its edges to whole pixels.
+    pub fn fill_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, color: Vector4<f32>) {
+        let (left, top) = (x.round() as i64, y.round() as i64);
+        let (right, bottom) = ((x + width).round() as i64, (y + height).round() as i64);
+        for row in top.max(0)..bottom.min(self.height as i64) {
+            for column in left.max(0)..right.min(self.width as i64) {
+                self.blend(column, row, color, 1.);
+            }
+        }
+    }
+
+    /// Fills the circle, anti-aliasing its edge.
+    pub fn fill_circle(&mut self, x: f32, y: f32, radius: f32, color: Vector4<f32>) {
+        let (left, right) = (
+            (x - radius - 1.).floor() as i64,
+            (x + radius + 1.).ceil() as i64,
+        );
+        let (top, bottom) = (
+            (y - radius - 1.).floor() as i64,
+            (y + radius + 1.).ceil() as i64,
+        );
+        for row in top..bottom {
+            for column in left..right {
+                let distance =
+                    ((column as f32 + 0.5 - x).powi(2) + (row as f32 + 0.5 - y).powi(2)).sqrt();
+                let coverage = (radius + 0.5 - distance).clamp(0., 1.);
+                if coverage > 0. {
+                    self.blend(column, row, color, coverage);
+                }
+            }
+        }
+    }
+
+    /// The pixels as 8-bit RGBA.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| {
+                pixel
+                    .iter()
+                    .map(|&c| (c.clamp(0., 1.) * 255.).round() as u8)
+            })
+            .collect()
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), png::EncodingError> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgba8())?;
+        writer.finish()
+    }
+}
+
+/// Draws the whole world into a new `width` by `height` canvas, like the world view of the window.
+///
+/// The terrain is drawn as the background, creatures are colored by the same [`ColorScheme`] and the heatmap is drawn if given and enabled in `graphics`.
+/// Text, such as the legend, the info panel and the charts, is not drawn.
+pub fn render(
+    state: &State,
+    graphics: &Graphics,
+    heatmap: Option<&Heatmap>,
+    width: u32,
+    height: u32,
+) -> Canvas {
+    let config = state.config();
+    let mut canvas = Canvas::new(width, height, graphics.background_color);
+    let zoom = (width as f32 / config.world_width()).min(height as f32 / config.world_height());
+    let to_canvas = |location: Location| {
+        let offset = (location - config.upper_left()) * zoom;
+        (offset.x, offset.y)
+    };
+
+    canvas.fill_rectangle(
+        0.,
+        0.,
+        config.world_width() * zoom,
+        config.world_height() * zoom,
+        graphics.world_color,
+    );
+
+    let terrain = state.terrain();
+    let cell_size = terrain.cell_size() * zoom;
+    for row in 0..terrain.rows() {
+        for column in 0..terrain.columns() {
+            let ground = terrain.ground(column, row);
+            if ground != Ground::Open {
+                canvas.fill_rectangle(
+                    column as f32 * cell_size,
+                    row as f32 * cell_size,
+                    cell_size.min(config.world_width() * zoom - column as f32 * cell_size),
+                    cell_size.min(config.world_height() * zoom - row as f32 * cell_size),
+                    graphics.ground_color(ground),
+                );
+            }
+        }
+    }
+
+    if let Some(heatmap) = heatmap.filter(|_| graphics.show_heatmap) {
+        let cell_size = heatmap.cell_size() * zoom;
+        for row in 0..heatmap.rows() {
+            for column in 0..heatmap.columns() {
+                if let Some(color) = heatmap.color(column, row, graphics.heatmap_opacity) {
+                    canvas.fill_rectangle(
+                        column as f32 * cell_size,
+                        row as f32 * cell_size,
+                        cell_size,
+                        cell_size,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+
+    let color_scheme = ColorScheme::new(state, graphics);
+    for entity in state.entities() {
+        let color = match entity.entity_data() {
+            EntityData::Creature(creature) => color_scheme.creature_color(creature),
+            EntityData::Food(_) => graphics.food_color,
+        };
+        let (x, y) = to_canvas(entity.location());
+        canvas.fill_circle(x, y, entity.size(config) * 0.5 * zoom, color);
+    }
+    canvas
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    pub fn render_fits_world_and_draws_creatures() {
+        let config = Config::default();
+        let state = State::init(config.clone(), 1);
+        let canvas = render(&state, &config.graphics, None, 200, 100);
+        assert_eq!((canvas.width(), canvas.height()), (200, 100));
+        // The square world fills the left half, the rest is background.
+        assert_eq!(canvas.pixel(150, 50), config.graphics.background_color);
+        let creature = state.entities().next().unwrap();
+        let x = (creature.location().x() * 100. / config.world_width()) as u32;
+        let y = (creature.location().y() * 100. / config.world_height()) as u32;
+        let pixel = canvas.pixel(x, y);
+        assert_ne!(pixel, config.graphics.world_color);
+        assert_eq!(canvas.to_rgba8().len(), 200 * 100 * 4);
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    coloring::ColorScheme,
+    config::Graphics,
+    world::{EntityData, EntityId, EntityType, Location},
+    Config, Event, State,
+};
+
+/// Version of the replay file format. Files of other versions are rejected.
+///
+/// Bump whenever the serialized layout of a replay changes, including the layout of [`Config`] and [`Event`].
+const FORMAT_VERSION: u32 = 1;
+
+/// What is needed to draw an entity in a replay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntitySnapshot {
+    pub id: EntityId,
+    pub entity_type: EntityType,
+    pub location: Location,
+    pub size: f32,
+    /// The color the entity was drawn with while recording, as 8-bit RGBA.
+    pub color: [u8; 4],
+}
+
+/// The entities at one tick and the events since the previous frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Frame {
+    pub tick: u64,
+    pub entities: Vec<EntitySnapshot>,
+    pub events: Vec<Event>,
+}
+
+impl Frame {
+    pub fn creatures(&self) -> usize {
+        self.entities
+            .iter()
+            .filter(|entity| entity.entity_type == EntityType::Creature)
+            .count()
+    }
+
+    pub fn food(&self) -> usize {
+        self.entities.len() - self.creatures()
+    }
+}
+
+/// A recording of a run that can be played back without simulating it again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    config: Config,
+    /// Number of ticks between frames.
+    interval: u64,
+    frames: Vec<Frame>,
+    /// Events since the last frame.
+    #[serde(skip)]
+    pending_events: Vec<Event>,
+    /// The tick the last events were recorded at.
+    #[serde(skip)]
+    last_tick: Option<u64>,
+}
+
+impl Replay {
+    /// An empty replay of a run with the given config, keeping a frame every `interval` ticks.
+    pub fn new(config: Config, interval: u64) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            config,
+            interval: interval.max(1),
+            frames: Vec::new(),
+            pending_events: Vec::new(),
+            last_tick: None,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Number of ticks between frames.
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// The index of the last frame at or before `tick`.
+    pub fn frame_index_at(&self, tick: u64) -> usize {
+        self.frames
+            .partition_point(|frame| frame.tick <= tick)
+            .saturating_sub(1)
+    }
+
+    /// Keeps the events of the last tick and adds a frame if one is due.
+    ///
+    /// Call after every tick so no events are missed. Creatures are colored as in the window with the given graphics.
+    pub fn record(&mut self, state: &State, graphics: &Graphics) {
+        if self.last_tick == Some(state.tick_count()) {
+            return;
+        }
+        self.last_tick = Some(state.tick_count());
+        self.pending_events.extend_from_slice(state.events());
+        if state.tick_count().is_multiple_of(self.interval) {
+            self.push_frame(state, graphics);
+        }
+    }
+
+    /// Records the final state, adding a frame for it even if none is due, so replays always end where the run did.
+    ///
+    /// Call once after the last tick.
+    pub fn finish(&mut self, state: &State, graphics: &Graphics) {
+        self.record(state, graphics);
+        if self.frames.last().map(|frame| frame.tick) != Some(state.tick_count()) {
+            self.push_frame(state, graphics);
+        }
+    }
+
+    fn push_frame(&mut self, state: &State, graphics: &Graphics) {
+        let color_scheme = ColorScheme::new(state, graphics);
+        let entities = state
+            .entities()
+            .map(|entity| {
+                let color = match entity.entity_data() {
+                    EntityData::Creature(creature) => color_scheme.creature_color(creature),
+                    EntityData::Food(_) => graphics.food_color,
+                };
+                EntitySnapshot {
+                    id: entity.id(),
+                    entity_type: entity.entity_type(),
+                    location: entity.location(),
+                    size: entity.size(state.config()),
+                    color: color.map(|c| (c.clamp(0., 1.) * 255.).round() as u8).into(),
+                }
+            })
+            .collect();
+        self.frames.push(Frame {
+            tick: state.tick_count(),
+            entities,
+            events: std::mem::take(&mut self.pending_events),
+        });
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> bincode::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::DefaultOptions::new().serialize_into(writer, self)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> bincode::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let replay: Self = bincode::DefaultOptions::new().deserialize_from(reader)?;
+        if replay.version != FORMAT_VERSION {
+            return Err(Box::new(bincode::ErrorKind::Custom(format!(
+                "unsupported replay version {}, expected {FORMAT_VERSION}",
+                replay.version
+            ))));
+        }
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn replay_round_trips() {
+        let config = Config::default();
+        let mut state = State::init(config.clone(), 5);
+        let mut replay = Replay::new(config.clone(), 10);
+        replay.record(&state, &config.graphics);
+        for _ in 0..95 {
+            state.tick();
+            replay.record(&state, &config.graphics);
+        }
+        assert_eq!(replay.frames().len(), 10);
+        replay.finish(&state, &config.graphics);
+        assert_eq!(replay.frames().len(), 11);
+        assert_eq!(replay.frames()[10].tick, 95);
+        assert_eq!(replay.frame_index_at(55), 5);
+        assert_eq!(replay.frame_index_at(1000), 10);
+
+        let path =
+            std::env::temp_dir().join(format!("replay_round_trips_{}.bin", std::process::id()));
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.interval(), 10);
+        assert_eq!(loaded.frames().len(), replay.frames().len());
+        let (last, loaded_last) = (&replay.frames()[10], &loaded.frames()[10]);
+        assert_eq!(loaded_last.tick, 95);
+        assert_eq!(loaded_last.entities.len(), last.entities.len());
+        assert_eq!(loaded_last.entities[0].location, last.entities[0].location);
+        assert_eq!(loaded_last.events, last.events);
+    }
+
+    /// Describes the layout of the value as one line per field with its path and JSON type, ignoring the values.
+    ///
+    /// Items of the same array share a path, so consecutive items with the same layout repeat lines.
+    fn layout(value: &serde_json::Value, path: &str, lines: &mut Vec<String>) {
+        use serde_json::Value;
+        match value {
+            Value::Object(fields) => {
+                for (name, field) in fields {
+                    layout(field, &format!("{path}.{name}"), lines);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    layout(item, &format!("{path}[]"), lines);
+                }
+            }
+            Value::Null => lines.push(format!("{path}: null")),
+            Value::Bool(_) => lines.push(format!("{path}: bool")),
+            Value::Number(_) => lines.push(format!("{path}: number")),
+            Value::String(_) => lines.push(format!("{path}: string")),
+        }
+    }
+
+    #[test]
+    pub fn format_version_tracks_layout() {
+        let mut ids = crate::world::EntityIdGenerator::new();
+        let (id, other) = (ids.next_id(), ids.next_id());
+        let location = Location::new(1., 2.);
+        let mut replay = Replay::new(Config::default(), 10);
+        replay.frames.push(Frame {
+            tick: 0,
+            entities: vec![EntitySnapshot {
+                id,
+                entity_type: EntityType::Creature,
+                location,
+                size: 1.,
+                color: [0; 4],
+            }],
+            events: vec![
+                Event::Birth {
+                    id,
+                    parent: other,
+                    location,
+                },
+                Event::Death {
+                    id,
+                    location,
+                    cause: crate::DeathCause::Starvation,
+                    carcass: Some(other),
+                },
+                Event::Eaten {
+                    food: other,
+                    creature: id,
+                    location,
+                },
+            ],
+        });
+        let mut lines = Vec::new();
+        layout(&serde_json::to_value(&replay).unwrap(), "", &mut lines);
+        lines.dedup();
+        // FNV-1a, which unlike the standard hasher is stable across Rust versions.
+        let fingerprint = lines
+            .join("\n")
+            .bytes()
+            .fold(0xcbf29ce484222325u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            });
+        assert_eq!(
+            (FORMAT_VERSION, fingerprint),
+            (1, 0xf0f6634475260c83),
+            "the replay layout changed, bump FORMAT_VERSION and update the fingerprint:\n{}",
+            lines.join("\n")
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::{fmt, time::Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{world::CreatureTrait, Sample, State};
+
+/// A reason to stop ticking a [`Runner`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StopCondition {
+    /// The state has reached the given tick count.
+    MaxTicks(u64),
+    /// No creatures are left.
+    Extinction,
+    /// More creatures than the given number are alive.
+    PopulationAbove(usize),
+    /// The mean of the trait over all creatures is above `threshold`.
+    MeanTraitAbove {
+        creature_trait: CreatureTrait,
+        threshold: f32,
+    },
+    /// The mean of the trait over all creatures is below `threshold`.
+    MeanTraitBelow {
+        creature_trait: CreatureTrait,
+        threshold: f32,
+    },
+    /// The runner has spent the given real seconds ticking. Not available on the web.
+    WallClock(f64),
+}
+
+impl StopCondition {
+    fn is_met(&self, state: &State, sample: &Sample, seconds: f64) -> bool {
+        match *self {
+            StopCondition::MaxTicks(ticks) => state.tick_count() >= ticks,
+            StopCondition::Extinction => sample.creatures() == 0,
+            StopCondition::PopulationAbove(cap) => sample.creatures() > cap,
+            StopCondition::MeanTraitAbove {
+                creature_trait,
+                threshold,
+            } => sample.creatures() > 0 && sample.trait_mean(creature_trait) > threshold,
+            StopCondition::MeanTraitBelow {
+                creature_trait,
+                threshold,
+            } => sample.creatures() > 0 && sample.trait_mean(creature_trait) < threshold,
+            StopCondition::WallClock(budget) => seconds >= budget,
+        }
+    }
+}
+
+impl fmt::Display for StopCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopCondition::MaxTicks(ticks) => write!(f, "reached {ticks} ticks"),
+            StopCondition::Extinction => write!(f, "extinction"),
+            StopCondition::PopulationAbove(cap) => write!(f, "population above {cap}"),
+            StopCondition::MeanTraitAbove {
+                creature_trait,
+                threshold,
+            } => write!(f, "mean {creature_trait} above {threshold}"),
+            StopCondition::MeanTraitBelow {
+                creature_trait,
+                threshold,
+            } => write!(f, "mean {creature_trait} below {threshold}"),
+            StopCondition::WallClock(budget) => write!(f, "used {budget} s wall clock budget"),
+        }
+    }
+}
+
+/// Why and when a run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Termination {
+    /// The first condition that was met.
+    pub reason: StopCondition,
+    pub tick: u64,
+}
+
+/// Ticks a state until one of its stop conditions is met.
+pub struct Runner {
+    state: State,
+    conditions: Vec<StopCondition>,
+    /// When the runner first ticked. Only tracked with a wall clock condition, as the clock is not available on the
+    /// web.
+    started: Option<Instant>,
+    /// Whether the conditions have been checked against the current state.
+    checked: bool,
+    termination: Option<Termination>,
+}
+
+impl Runner {
+    pub fn new(state: State, conditions: Vec<StopCondition>) -> Self {
+        Self {
+            state,
+            conditions,
+            started: None,
+            checked: false,
+            termination: None,
+        }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn into_state(self) -> State {
+        self.state
+    }
+
+    pub fn conditions(&self) -> &[StopCondition] {
+        &self.conditions
+    }
+
+    /// Why the runner stopped. `None` while it is still running.
+    pub fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
+
+    /// The first stop condition that is met by the current state, in the order they were given.
+    pub fn check(&self) -> Option<StopCondition> {
+        let sample = Sample::new(&self.state);
+        let seconds = self
+            .started
+            .map_or(0., |started| started.elapsed().as_secs_f64());
+        self.conditions
+            .iter()
+            .find(|condition| condition.is_met(&self.state, &sample, seconds))
+            .copied()
+    }
+
+    /// Ticks once unless the runner has already stopped, returning the termination if it stops now or already had.
+    pub fn step(&mut self) -> Option<Termination> {
+        if !self.checked {
+            self.update_termination();
+        }
+        if self.termination.is_some() {
+            return self.termination;
+        }
+        let timed = self
+            .conditions
+            .iter()
+            .any(|condition| matches!(condition, StopCondition::WallClock(_)));
+        if timed && self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+        self.state.tick();
+        self.update_termination();
+        self.termination
+    }
+
+    /// Checks the conditions against the current state once.
+    fn update_termination(&mut self) {
+        self.termination = self.check().map(|reason| Termination {
+            reason,
+            tick: self.state.tick_count(),
+        });
+        self.checked = true;
+    }
+
+    /// Ticks until a stop condition is met. Never returns if none of the conditions is ever met.
+    pub fn run(&mut self) -> Termination {
+        loop {
+            if let Some(termination) = self.step() {
+                return termination;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    pub fn runner_reports_first_met_condition() {
+        let mut runner = Runner::new(
+            State::init(Config::default(), 0),
+            vec![StopCondition::MaxTicks(10), StopCondition::Extinction],
+        );
+        assert_eq!(
+            runner.run(),
+            Termination {
+                reason: StopCondition::Extinction,
+                tick: 0
+            }
+        );
+        assert_eq!(runner.state().tick_count(), 0);
+
+        let mut runner = Runner::new(
+            State::init(Config::default(), 3),
+            vec![StopCondition::Extinction, StopCondition::MaxTicks(10)],
+        );
+        assert_eq!(runner.run().reason, StopCondition::MaxTicks(10));
+        assert_eq!(runner.state().tick_count(), 10);
+        assert_eq!(runner.step().map(|termination| termination.tick), Some(10));
+    }
+}
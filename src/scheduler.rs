@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use crate::{
+    world::{Entity, EntityId, Location},
+    State,
+};
+
+/// What happened during one frame of a [`Scheduler`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTicks {
+    /// Number of ticks run this frame.
+    pub ticks: usize,
+    /// Whether simulated time was dropped because the ticks could not keep up with the simulation rate.
+    pub fell_behind: bool,
+}
+
+/// Runs ticks at a fixed simulation rate independent of the frame rate.
+///
+/// Real time accumulates every frame and is spent on whole ticks, limited by a tick count and a real time budget per
+/// frame so slow ticks cannot stall rendering. Locations are interpolated between the last two ticks by the time left
+/// over, so entities move smoothly even when there are fewer ticks than frames.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    /// Real seconds per tick. `None` ticks as often as the budget allows.
+    seconds_per_tick: Option<f64>,
+    /// Most ticks run in one frame.
+    max_ticks_per_frame: usize,
+    /// Most real seconds spent ticking in one frame.
+    tick_budget: f64,
+    /// Real seconds not yet spent on ticks.
+    accumulator: f64,
+    /// Locations before the last tick. Empty when not interpolating.
+    previous_locations: HashMap<EntityId, Location>,
+}
+
+impl Scheduler {
+    pub fn new(
+        seconds_per_tick: Option<f64>,
+        max_ticks_per_frame: usize,
+        tick_budget: f64,
+    ) -> Self {
+        Self {
+            seconds_per_tick,
+            max_ticks_per_frame,
+            tick_budget,
+            accumulator: 0.,
+            previous_locations: HashMap::new(),
+        }
+    }
+
+    pub fn seconds_per_tick(&self) -> Option<f64> {
+        self.seconds_per_tick
+    }
+
+    /// Changes the simulation rate, keeping at most one tick of accumulated time.
+    pub fn set_seconds_per_tick(&mut self, seconds_per_tick: Option<f64>) {
+        if seconds_per_tick != self.seconds_per_tick {
+            self.seconds_per_tick = seconds_per_tick;
+            self.accumulator = seconds_per_tick.map_or(0., |spt| self.accumulator.min(spt));
+        }
+    }
+
+    /// How far the simulation has progressed from the last tick towards the next one, in `[0, 1]`.
+    pub fn alpha(&self) -> f32 {
+        match self.seconds_per_tick {
+            Some(spt) if spt > 0. => (self.accumulator / spt).clamp(0., 1.) as f32,
+            _ => 1.,
+        }
+    }
+
+    /// Spends `frame_time` real seconds on ticks, calling `on_tick` after each one.
+    ///
+    /// `clock` returns the current real time in seconds and enforces the tick budget.
+    pub fn frame(
+        &mut self,
+        state: &mut State,
+        frame_time: f64,
+        clock: impl Fn() -> f64,
+        mut on_tick: impl FnMut(&State),
+    ) -> FrameTicks {
+        let start = clock();
+        let mut ticks = 0;
+        match self.seconds_per_tick {
+            Some(spt) => {
+                self.accumulator += frame_time;
+                while self.accumulator >= spt
+                    && ticks < self.max_ticks_per_frame
+                    && clock() - start < self.tick_budget
+                {
+                    self.tick(state, &mut on_tick);
+                    self.accumulator -= spt;
+                    ticks += 1;
+                }
+                let fell_behind = self.accumulator >= spt;
+                if fell_behind {
+                    self.accumulator %= spt;
+                }
+                FrameTicks { ticks, fell_behind }
+            }
+            None => {
+                while ticks == 0
+                    || (ticks < self.max_ticks_per_frame && clock() - start < self.tick_budget)
+                {
+                    self.tick(state, &mut on_tick);
+                    ticks += 1;
+                }
+                FrameTicks {
+                    ticks,
+                    fell_behind: false,
+                }
+            }
+        }
+    }
+
+    /// Runs a single tick regardless of the simulation rate, e.g. to step while paused.
+    pub fn step(&mut self, state: &mut State, mut on_tick: impl FnMut(&State)) {
+        self.tick(state, &mut on_tick);
+        self.reset();
+    }
+
+    /// Drops accumulated time and stops interpolating, e.g. while paused, so resuming does not catch up and entities
+    /// are shown where they are.
+    pub fn reset(&mut self) {
+        self.accumulator = 0.;
+        self.previous_locations.clear();
+    }
+
+    fn tick(&mut self, state: &mut State, on_tick: &mut impl FnMut(&State)) {
+        self.previous_locations.clear();
+        // Without a fixed rate entities are always shown where they are.
+        if self.seconds_per_tick.is_some() {
+            self.previous_locations.extend(
+                state
+                    .entities()
+                    .map(|entity| (entity.id(), entity.location())),
+            );
+        }
+        state.tick();
+        on_tick(state);
+    }
+
+    /// The location of the entity between the last two ticks according to [`Scheduler::alpha`].
+    ///
+    /// Entities that did not exist before the last tick are at their current location.
+    pub fn interpolated_location(&self, entity: &Entity) -> Location {
+        match self.previous_locations.get(&entity.id()) {
+            Some(&previous) if self.seconds_per_tick.is_some() => {
+                previous + (entity.location() - previous) * self.alpha()
+            }
+            _ => entity.location(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    pub fn scheduler_ticks_at_fixed_rate_within_budget() {
+        let mut state = State::init(Config::default(), 3);
+        let mut scheduler = Scheduler::new(Some(0.1), 5, 1.);
+        let clock = || 0.;
+
+        let ticks = scheduler.frame(&mut state, 0.25, clock, |_| {});
+        assert_eq!(
+            ticks,
+            FrameTicks {
+                ticks: 2,
+                fell_behind: false
+            }
+        );
+        assert!((scheduler.alpha() - 0.5).abs() < 1e-6);
+
+        // Falling far behind runs at most `max_ticks_per_frame` ticks and drops the rest.
+        let ticks = scheduler.frame(&mut state, 10., clock, |_| {});
+        assert_eq!(
+            ticks,
+            FrameTicks {
+                ticks: 5,
+                fell_behind: true
+            }
+        );
+        assert_eq!(state.tick_count(), 7);
+        assert!(scheduler.alpha() < 1.);
+
+        // A slow clock stops ticking once the budget is used up.
+        let time = Cell::new(0.);
+        let clock = || {
+            time.set(time.get() + 0.6);
+            time.get()
+        };
+        let ticks = scheduler.frame(&mut state, 0.5, clock, |_| {});
+        assert_eq!(ticks.ticks, 1);
+
+        let entity = state.entities().next().unwrap();
+        let previous = scheduler.previous_locations[&entity.id()];
+        let expected = previous + (entity.location() - previous) * scheduler.alpha();
+        assert_eq!(scheduler.interpolated_location(entity), expected);
+
+        scheduler.set_seconds_per_tick(None);
+        scheduler.frame(&mut state, 0.5, || 0., |_| {});
+        assert!(scheduler.previous_locations.is_empty());
+    }
+}
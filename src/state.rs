@@ -1,117 +1,373 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
 use rand_distr::Distribution;
 use rand_pcg::Pcg64Mcg;
 
 use crate::{
-    world::{Entity, EntityData, Location},
-    Config,
+    world::{
+        Entity, EntityData, EntityId, EntityIdGenerator, EnvironmentField, Location, TerrainGrid,
+    },
+    Config, DeathCause, EnergyLedger, Event, History, Sample,
 };
 
 pub struct State {
     config: Config,
+    terrain: TerrainGrid,
     entities: Vec<Entity>,
     tick_count: u64,
     rng: Pcg64Mcg,
+    entity_ids: EntityIdGenerator,
+    energy_ledger: EnergyLedger,
+    events: Vec<Event>,
+    history: History,
 }
 
 impl State {
     pub fn init(config: Config, num_creatures: usize) -> Self {
-        let mut rng = Pcg64Mcg::new(config.rng_seed());
+        let mut rng = Pcg64Mcg::seed_from_u64(config.rng_seed());
+        let mut entity_ids = EntityIdGenerator::new();
+        let terrain = TerrainGrid::generate(&config);
 
-        let entities = (0..num_creatures)
-            .map(|_| {
-                Entity::creature(
+        // No creatures fit in a world that is impassable everywhere.
+        let entities: Vec<_> = (0..num_creatures)
+            .map_while(|_| {
+                let location = terrain.random_location(&config, &mut rng)?;
+                Some(Entity::creature(
                     &config,
-                    Location::new(
-                        rng.gen_range(0.0..config.world_width()),
-                        rng.gen_range(0.0..config.world_height()),
-                    ),
-                )
+                    entity_ids.next_id(),
+                    location,
+                    &mut rng,
+                ))
             })
             .collect();
 
-        Self {
+        let energy_ledger = EnergyLedger::new(total_energy(&entities));
+
+        let history = History::new(config.history_interval(), config.history_length());
+
+        let mut state = Self {
             config,
+            terrain,
             entities,
             tick_count: 0,
             rng,
-        }
+            entity_ids,
+            energy_ledger,
+            events: Vec::new(),
+            history,
+        };
+        state.record_history();
+        state
     }
 
     pub fn config(&self) -> &Config {
         &self.config
     }
 
+    pub fn terrain(&self) -> &TerrainGrid {
+        &self.terrain
+    }
+
     pub fn tick_count(&self) -> u64 {
         self.tick_count
     }
 
+    /// Simulated seconds since the start.
+    pub fn time(&self) -> f32 {
+        self.tick_count as f32 * self.config.tick_length()
+    }
+
+    /// The value of the environment field at the location.
+    pub fn environment_value(&self, field: EnvironmentField, location: Location) -> f32 {
+        self.config
+            .environment
+            .field(field)
+            .value(&self.config, location, self.time())
+    }
+
     pub fn entities(&self) -> impl ExactSizeIterator<Item = &Entity> {
         self.entities.iter()
     }
 
-    fn spawn_food(&mut self) {
-        let poisson =
-            rand_distr::Poisson::new(self.config.food_spawn_rate() * self.config.tick_length())
-                .unwrap(); // Both food spawn rate and tick length should always be positive and non-infinite.
+    /// Finds the entity with the given id if it still exists.
+    pub fn entity(&self, id: EntityId) -> Option<&Entity> {
+        self.entities.iter().find(|entity| entity.id() == id)
+    }
+
+    /// The energy flows of the last tick.
+    pub fn energy_ledger(&self) -> &EnergyLedger {
+        &self.energy_ledger
+    }
+
+    /// Total energy in the world, i.e. energy stored in creatures plus energy in food.
+    pub fn total_energy(&self) -> f64 {
+        total_energy(&self.entities)
+    }
+
+    /// The events of the last tick.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Samples of the state taken at regular intervals.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    fn record_history(&mut self) {
+        if self.history.is_due(self.tick_count) {
+            let sample = Sample::new(self);
+            self.history.push(sample);
+        }
+    }
+
+    /// Spawns food in proportion to the light.
+    ///
+    /// Candidates are spawned as if there was maximum light everywhere and then thinned out by the actual light.
+    fn spawn_food(&mut self, ledger: &mut EnergyLedger) {
+        let max_light = self.config.environment.light.max();
+        let expected_spawn = self.config.food_spawn_rate() * self.config.tick_length() * max_light;
+        if expected_spawn <= 0. {
+            return;
+        }
+        let poisson = rand_distr::Poisson::new(expected_spawn).unwrap(); // Food spawn rate and tick length should always be non-infinite.
         let num_spawn = poisson.sample(&mut self.rng) as u64;
         for _ in 0..num_spawn {
-            self.entities.push(Entity::food(Location::new(
-                self.rng.gen_range(0.0..self.config.world_width()),
-                self.rng.gen_range(0.0..self.config.world_height()),
-            )));
+            let Some(location) = self.terrain.random_location(&self.config, &mut self.rng) else {
+                return;
+            };
+            let light = self.environment_value(EnvironmentField::Light, location);
+            if light < max_light && self.rng.gen::<f32>() * max_light >= light {
+                continue;
+            }
+            let food = Entity::food(&self.config, self.entity_ids.next_id(), location);
+            ledger.record_food_spawned(food.energy());
+            self.entities.push(food);
         }
     }
 
     pub fn tick(&mut self) {
-        self.spawn_food();
+        let mut ledger = EnergyLedger::new(self.total_energy());
+        let mut events = Vec::new();
+        self.spawn_food(&mut ledger);
         // Tick entities.
-        let new_entities: Vec<_> = self.entities.iter().map(|e| e.tick(self)).collect();
+        let mut tick_rng = Pcg64Mcg::new(self.rng.gen());
+        let new_entities: Vec<_> = self
+            .entities
+            .iter()
+            .map(|e| e.tick(self, &mut tick_rng))
+            .collect();
+        for entity in new_entities.iter() {
+            if let EntityData::Creature(creature) = entity.entity_data() {
+                ledger.record_metabolism(creature.metabolic_cost());
+            }
+        }
 
         // Eat food.
         let mut energy_eaten = vec![(true, 0.); new_entities.len()];
-        for (food_index, food) in new_entities
-            .iter()
-            .enumerate()
-            .filter(|(_, entity)| entity.is_food())
-        {
+        for (food_index, food) in new_entities.iter().enumerate() {
+            let EntityData::Food(food_data) = food.entity_data() else {
+                continue;
+            };
             if let Some(creature_index) = new_entities.iter().position(|other| {
                 other.is_creature()
                     && (food.location() - other.location()).norm_squared()
-                        < self.config().entity_size().powi(2)
+                        < ((food.size(&self.config) + other.size(&self.config)) * 0.5).powi(2)
             }) {
-                energy_eaten[creature_index].1 += 3.;
+                energy_eaten[creature_index].1 += food_data.energy();
                 energy_eaten[food_index].0 = false;
+                events.push(Event::Eaten {
+                    food: food.id(),
+                    creature: new_entities[creature_index].id(),
+                    location: food.location(),
+                });
             }
         }
-        // Feed creatures.
-        let new_entities: Vec<_> = new_entities
-            .into_iter()
-            .zip(energy_eaten)
-            .filter_map(|(entity, (survived, energy))| {
-                if survived {
-                    Some(if energy != 0. {
-                        entity.eat(&self.config, energy)
-                    } else {
-                        entity
-                    })
-                } else {
-                    None
+
+        let mut entities = Vec::with_capacity(new_entities.len());
+        for (entity, (survived, energy)) in new_entities.into_iter().zip(energy_eaten) {
+            if !survived {
+                continue;
+            }
+            // Feed creatures.
+            let entity = if energy != 0. {
+                let (fed, overflow) = entity.eat(&self.config, energy);
+                ledger.record_eating(energy, overflow);
+                fed
+            } else {
+                entity
+            };
+            // Kill creatures with no energy or of old age, leaving their carcasses behind.
+            let cause = match entity.entity_data() {
+                EntityData::Creature(creature) if creature.energy() <= 0. => {
+                    Some(DeathCause::Starvation)
                 }
-            })
-            // Kill creatures with no energy.
-            .filter(|entity| {
-                if let EntityData::Creature(creature) = entity.entity_data() {
-                    creature.energy() > 0.
-                } else {
-                    true
+                EntityData::Creature(creature)
+                    if creature.dies_of_old_age(&self.config, &mut self.rng) =>
+                {
+                    Some(DeathCause::OldAge)
                 }
-            })
+                _ => None,
+            };
+            let entity = match cause {
+                Some(cause) => {
+                    ledger.record_death(entity.energy());
+                    let carcass = entity.carcass(&self.config, &mut self.entity_ids);
+                    events.push(Event::Death {
+                        id: entity.id(),
+                        location: entity.location(),
+                        cause,
+                        carcass: carcass.as_ref().map(Entity::id),
+                    });
+                    match carcass {
+                        Some(carcass) => {
+                            ledger.record_carcass(carcass.energy());
+                            carcass
+                        }
+                        None => continue,
+                    }
+                }
+                None => entity,
+            };
             // Reproduce.
-            .flat_map(|entity| entity.reproduce(&self.config, &mut self.rng))
-            .collect();
-        self.entities = new_entities;
+            let parent = entity.id();
+            let first_child = entities.len();
+            let (offspring, cost) =
+                entity.reproduce(&self.config, &mut self.rng, &mut self.entity_ids);
+            entities.extend(offspring);
+            ledger.record_reproduction(cost);
+            let children = &entities[first_child..];
+            events.extend(
+                children
+                    .iter()
+                    .filter(|child| child.id() != parent)
+                    .map(|child| Event::Birth {
+                        id: child.id(),
+                        parent,
+                        location: child.location(),
+                    }),
+            );
+        }
+        self.entities = entities;
+        self.events = events;
+
+        ledger.close(self.total_energy());
+        debug_assert!(
+            ledger.is_balanced(),
+            "Energy ledger does not balance. Imbalance: {}. Ledger: {ledger:?}",
+            ledger.imbalance()
+        );
+        self.energy_ledger = ledger;
 
         self.tick_count += 1;
+        self.record_history();
+    }
+}
+
+fn total_energy(entities: &[Entity]) -> f64 {
+    entities.iter().map(|entity| entity.energy() as f64).sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::{Mortality, ReproductionMode};
+    use crate::world::FieldLayout;
+
+    #[test]
+    pub fn energy_ledger_balances() {
+        let mut budding = Config::default();
+        budding.reproduction.mode = ReproductionMode::Budding;
+        budding.reproduction.cost = 5.;
+        for config in [Config::default(), budding] {
+            let mut state = State::init(config, 10);
+            for _ in 0..2000 {
+                state.tick();
+                let ledger = state.energy_ledger();
+                assert!(
+                    ledger.is_balanced(),
+                    "tick {}: {ledger:?}",
+                    state.tick_count()
+                );
+                assert_eq!(ledger.closing_balance(), state.total_energy());
+            }
+        }
+    }
+
+    #[test]
+    pub fn creatures_die_of_old_age() {
+        let mut config = Config::default();
+        config.aging.mortality = Mortality::Lifespan;
+        config.aging.starting_lifespan = 1.;
+        let ticks = (2. / config.tick_length()) as usize;
+        let mut state = State::init(config, 5);
+        let mut deaths = 0;
+        for _ in 0..ticks {
+            state.tick();
+            for event in state.events() {
+                if let Event::Death { cause, .. } = event {
+                    assert_eq!(*cause, DeathCause::OldAge);
+                    assert!(state.time() >= 1.);
+                    deaths += 1;
+                }
+            }
+            assert!(state.energy_ledger().is_balanced());
+        }
+        assert_eq!(deaths, 5);
+        assert!(state.entities().all(Entity::is_food));
+    }
+
+    #[test]
+    pub fn food_spawns_in_proportion_to_light() {
+        let mut config = Config::default();
+        config.set("food_spawn_rate", "1000").unwrap();
+        config.environment.light.layout = FieldLayout::HorizontalGradient;
+        config.environment.light.amplitude = 1.;
+        let half_width = 0.5 * config.world_width();
+        let mut state = State::init(config, 0);
+        for _ in 0..20 {
+            state.tick();
+        }
+        let (left, right): (Vec<_>, Vec<_>) = state
+            .entities()
+            .filter(|entity| entity.is_food())
+            .partition(|food| food.location().x() < half_width);
+        // Light rises from 0 to 2 across the world, so the right half gets three times the food of the left half.
+        let ratio = right.len() as f32 / left.len() as f32;
+        assert!(
+            (2.5..3.5).contains(&ratio),
+            "{} vs {}",
+            left.len(),
+            right.len()
+        );
+    }
+
+    #[test]
+    pub fn starving_creatures_leave_carcasses() {
+        let mut config = Config::default();
+        config.set("creature_starting_energy", "0.001").unwrap();
+        config.set("food_spawn_rate", "0").unwrap();
+        let mut state = State::init(config, 1);
+        let creature = state.entities().next().unwrap().id();
+        state.tick();
+
+        let [Event::Death {
+            id,
+            cause,
+            carcass: Some(carcass),
+            ..
+        }] = state.events()
+        else {
+            panic!("expected a single death: {:?}", state.events());
+        };
+        assert_eq!(*id, creature);
+        assert_eq!(*cause, DeathCause::Starvation);
+        let entities: Vec<_> = state.entities().collect();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].id(), *carcass);
+        assert!(entities[0].is_food());
+        // Half of the body energy of a creature of unit mass, and none stored.
+        assert_eq!(entities[0].energy(), 10.);
+        assert_eq!(state.energy_ledger().carcasses(), 10.);
+        assert!(state.energy_ledger().is_balanced());
     }
 }
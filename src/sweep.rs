@@ -0,0 +1,355 @@
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    config::ConfigError,
+    runner::{Runner, StopCondition, Termination},
+    world::CreatureTrait,
+    Config, Sample, State,
+};
+
+/// The values a swept config field takes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ParameterRange {
+    /// Values as accepted by [`Config::set`]. Random sweeps pick one uniformly.
+    Values(Vec<String>),
+    /// `steps` evenly spaced numbers from `min` to `max` inclusive. Random sweeps pick any number in between.
+    Linear { min: f64, max: f64, steps: usize },
+}
+
+/// A config field and the values it is swept over.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Parameter {
+    /// Dot-separated path of the field as accepted by [`Config::set`].
+    pub key: String,
+    pub range: ParameterRange,
+}
+
+impl Parameter {
+    /// Every value of the range in order.
+    fn values(&self) -> Vec<String> {
+        match &self.range {
+            ParameterRange::Values(values) => values.clone(),
+            ParameterRange::Linear { min, max, steps } => (0..*steps)
+                .map(|step| {
+                    let t = if *steps > 1 {
+                        step as f64 / (steps - 1) as f64
+                    } else {
+                        0.
+                    };
+                    format_number(min + (max - min) * t)
+                })
+                .collect(),
+        }
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> String {
+        match &self.range {
+            ParameterRange::Values(values) => values.choose(rng).cloned().unwrap_or_default(),
+            ParameterRange::Linear { min, max, .. } => {
+                format_number(min + (max - min) * rng.gen::<f64>())
+            }
+        }
+    }
+}
+
+/// Formats a swept number with at most 6 decimals, so rounding errors like `0.30000000000000004` do not show up in
+/// the table.
+fn format_number(value: f64) -> String {
+    let formatted = format!("{value:.6}");
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    match formatted {
+        "-0" => "0".to_string(),
+        _ => formatted.to_string(),
+    }
+}
+
+/// How the combinations of parameter values are chosen.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum SweepMode {
+    /// Every combination of the values of all parameters.
+    #[default]
+    Grid,
+    /// `samples` combinations of independently sampled values.
+    Random { samples: usize, rng_seed: u64 },
+}
+
+/// A specification of a batch of headless runs: every chosen combination of parameter values is run once per seed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sweep {
+    pub parameters: Vec<Parameter>,
+    pub mode: SweepMode,
+    pub seeds: Vec<u64>,
+    /// Most ticks each run lasts.
+    pub ticks: u64,
+    /// Conditions that end a run before it reaches `ticks`.
+    pub stop_conditions: Vec<StopCondition>,
+    /// Number of creatures each run starts with.
+    pub creatures: usize,
+}
+
+impl Default for Sweep {
+    fn default() -> Self {
+        Self {
+            parameters: Vec::new(),
+            mode: SweepMode::default(),
+            seeds: vec![0],
+            ticks: 24 * 60 * 10,
+            stop_conditions: vec![StopCondition::Extinction],
+            creatures: 10,
+        }
+    }
+}
+
+/// A single run of a sweep.
+#[derive(Debug, Clone)]
+pub struct SweepRun {
+    /// The key and value of each parameter, in the order of the sweep's parameters.
+    pub assignments: Vec<(String, String)>,
+    pub seed: u64,
+    pub config: Config,
+}
+
+/// End-of-run metrics of a single run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub termination: Termination,
+    /// The state at the end of the run.
+    pub last: Sample,
+    pub total_energy: f64,
+    /// Most creatures alive at once.
+    pub peak_creatures: usize,
+    /// Mean number of creatures alive over all ticks of the run.
+    pub mean_creatures: f64,
+}
+
+impl Sweep {
+    /// The parameter values of every combination, in the order of the parameters.
+    pub fn combinations(&self) -> Vec<Vec<String>> {
+        match &self.mode {
+            SweepMode::Grid => self
+                .parameters
+                .iter()
+                .map(Parameter::values)
+                .multi_cartesian_product()
+                .collect(),
+            SweepMode::Random { samples, rng_seed } => {
+                let mut rng = Pcg64Mcg::seed_from_u64(*rng_seed);
+                (0..*samples)
+                    .map(|_| {
+                        self.parameters
+                            .iter()
+                            .map(|parameter| parameter.sample(&mut rng))
+                            .collect()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Every run of the sweep, applying the parameter values and seed to `base`.
+    pub fn runs(&self, base: &Config) -> Result<Vec<SweepRun>, ConfigError> {
+        let mut runs = Vec::new();
+        for values in self.combinations() {
+            let assignments: Vec<_> = self
+                .parameters
+                .iter()
+                .map(|parameter| parameter.key.clone())
+                .zip(values)
+                .collect();
+            for &seed in &self.seeds {
+                let mut config = base.clone();
+                for (key, value) in &assignments {
+                    config.set(key, value)?;
+                }
+                config.set("rng_seed", &seed.to_string())?;
+                config.validate()?;
+                runs.push(SweepRun {
+                    assignments: assignments.clone(),
+                    seed,
+                    config,
+                });
+            }
+        }
+        Ok(runs)
+    }
+
+    /// Runs every run of the sweep on `threads` threads, returning the summaries in the order of [`Sweep::runs`].
+    pub fn run(
+        &self,
+        base: &Config,
+        threads: usize,
+    ) -> Result<Vec<(SweepRun, RunSummary)>, ConfigError> {
+        let runs = self.runs(base)?;
+        let next_run = AtomicUsize::new(0);
+        let summaries = Mutex::new(vec![None; runs.len()]);
+        std::thread::scope(|scope| {
+            for _ in 0..threads.max(1) {
+                scope.spawn(|| loop {
+                    let index = next_run.fetch_add(1, Ordering::Relaxed);
+                    let Some(run) = runs.get(index) else {
+                        break;
+                    };
+                    let summary = self.run_one(run);
+                    summaries.lock().unwrap()[index] = Some(summary);
+                });
+            }
+        });
+        let summaries = summaries.into_inner().unwrap();
+        Ok(runs
+            .into_iter()
+            .zip(summaries)
+            .map(|(run, summary)| (run, summary.expect("Every run finishes")))
+            .collect())
+    }
+
+    fn run_one(&self, run: &SweepRun) -> RunSummary {
+        let conditions = self
+            .stop_conditions
+            .iter()
+            .copied()
+            .chain([StopCondition::MaxTicks(self.ticks)])
+            .collect();
+        let mut runner = Runner::new(State::init(run.config.clone(), self.creatures), conditions);
+        let count_creatures = |state: &State| {
+            state
+                .entities()
+                .filter(|entity| entity.is_creature())
+                .count()
+        };
+        let mut peak_creatures = count_creatures(runner.state());
+        let mut creature_ticks = 0;
+        let termination = loop {
+            let tick = runner.state().tick_count();
+            let termination = runner.step();
+            if runner.state().tick_count() > tick {
+                let creatures = count_creatures(runner.state());
+                peak_creatures = peak_creatures.max(creatures);
+                creature_ticks += creatures;
+            }
+            if let Some(termination) = termination {
+                break termination;
+            }
+        };
+        let state = runner.into_state();
+        RunSummary {
+            termination,
+            last: Sample::new(&state),
+            total_energy: state.total_energy(),
+            peak_creatures,
+            mean_creatures: creature_ticks as f64 / state.tick_count().max(1) as f64,
+        }
+    }
+}
+
+/// Writes one tab-separated row per run, with a column per parameter followed by the seed and the metrics.
+pub fn write_table(results: &[(SweepRun, RunSummary)], mut writer: impl Write) -> io::Result<()> {
+    let Some((first, _)) = results.first() else {
+        return Ok(());
+    };
+    let columns = first
+        .assignments
+        .iter()
+        .map(|(key, _)| key.clone())
+        .chain(
+            [
+                "seed",
+                "stop",
+                "tick",
+                "creatures",
+                "food",
+                "total_energy",
+                "peak_creatures",
+                "mean_creatures",
+            ]
+            .map(String::from),
+        )
+        .chain(CreatureTrait::iter().map(|creature_trait| format!("mean {creature_trait}")));
+    writeln!(writer, "{}", columns.format("\t"))?;
+    for (run, summary) in results {
+        let cells =
+            run.assignments
+                .iter()
+                .map(|(_, value)| value.clone())
+                .chain([
+                    run.seed.to_string(),
+                    summary.termination.reason.to_string(),
+                    summary.last.tick().to_string(),
+                    summary.last.creatures().to_string(),
+                    summary.last.food().to_string(),
+                    format!("{:.3}", summary.total_energy),
+                    summary.peak_creatures.to_string(),
+                    format!("{:.3}", summary.mean_creatures),
+                ])
+                .chain(CreatureTrait::iter().map(|creature_trait| {
+                    format!("{:.4}", summary.last.trait_mean(creature_trait))
+                }));
+        writeln!(writer, "{}", cells.format("\t"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn grid_runs_every_combination_per_seed() {
+        let sweep = Sweep {
+            parameters: vec![
+                Parameter {
+                    key: "drag".to_string(),
+                    range: ParameterRange::Linear {
+                        min: 0.,
+                        max: 0.2,
+                        steps: 3,
+                    },
+                },
+                Parameter {
+                    key: "creature_carcass_energy_fraction".to_string(),
+                    range: ParameterRange::Values(vec!["null".to_string(), "0.5".to_string()]),
+                },
+            ],
+            seeds: vec![1, 2],
+            ticks: 10,
+            creatures: 2,
+            ..Default::default()
+        };
+        let results = sweep.run(&Config::default(), 3).unwrap();
+        assert_eq!(results.len(), 3 * 2 * 2);
+        let (run, summary) = &results[5];
+        assert_eq!(run.assignments[0], ("drag".to_string(), "0.1".to_string()));
+        assert_eq!(run.config.drag(), 0.1);
+        assert_eq!(run.config.creature_carcass_energy_fraction(), None);
+        assert_eq!(run.seed, 2);
+        assert_eq!(summary.last.tick(), 10);
+        assert_eq!(summary.termination.reason, StopCondition::MaxTicks(10));
+
+        let mut table = Vec::new();
+        write_table(&results, &mut table).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert_eq!(table.lines().count(), 1 + results.len());
+        assert!(table.starts_with("drag\tcreature_carcass_energy_fraction\tseed\t"));
+    }
+
+    #[test]
+    pub fn swept_numbers_are_rounded() {
+        assert_eq!(format_number(0.1 + 0.2), "0.3");
+        assert_eq!(format_number(2.), "2");
+        assert_eq!(format_number(-0.25), "-0.25");
+        assert_eq!(format_number(-1e-9), "0");
+        assert_eq!(format_number(1234.5678901), "1234.56789");
+    }
+}
@@ -1,20 +1,57 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
 use rand::Rng;
 use rand_distr::{Distribution, Normal};
 use serde::{Deserialize, Serialize};
 
-use crate::{world::PhysicsBody, Config, State};
+use crate::{
+    config::{Mortality, ReproductionMode},
+    world::{EnvironmentField, Location, PhysicsBody, Vector},
+    Config, State,
+};
+
+use super::{
+    creature_trait::CreatureTrait, food::Food, metabolism::MetabolicCost, EntityId, Lineage,
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Creature {
     energy: f32,
     max_acceleration: f32,
+    size: f32,
+    sensing_range: f32,
+    field_of_view: f32,
+    preferred_temperature: f32,
+    temperature_tolerance: f32,
+    /// Seconds the creature lives if it dies of old age by lifespan. Scales how fast it ages otherwise.
+    lifespan: f32,
+    /// The direction the creature is facing as an angle in radians.
+    heading: f32,
+    /// Seconds since the creature was born.
+    age: f32,
+    lineage: Lineage,
+    /// The food the creature is currently going for.
+    target: Option<EntityId>,
+    metabolic_cost: MetabolicCost,
 }
 
 impl Creature {
-    pub fn new(config: &Config) -> Self {
+    /// A creature with the starting traits of the config, facing a random direction.
+    pub fn new(config: &Config, lineage: Lineage, rng: &mut impl Rng) -> Self {
         Self {
             energy: config.creature_starting_energy(),
             max_acceleration: 4.,
+            size: config.entity_size(),
+            sensing_range: config.creature_starting_sensing_range(),
+            field_of_view: config.creature_starting_field_of_view(),
+            preferred_temperature: config.environment.temperature.mean,
+            temperature_tolerance: config.creature_starting_temperature_tolerance(),
+            lifespan: config.aging.starting_lifespan,
+            heading: rng.gen_range(-PI..PI),
+            age: 0.,
+            lineage,
+            target: None,
+            metabolic_cost: MetabolicCost::default(),
         }
     }
 
@@ -22,61 +59,633 @@ impl Creature {
         self.energy
     }
 
+    /// The largest force the creature can exert.
+    ///
+    /// For a creature of the default entity size this is also its largest possible acceleration.
     pub fn max_acceleration(&self) -> f32 {
         self.max_acceleration
     }
 
-    pub fn tick(&self, body: &mut PhysicsBody, state: &State) -> Self {
-        if let Some(food) =
-            state
-                .entities()
-                .filter(|&entity| entity.is_food())
-                .min_by(|&entity1, &entity2| {
-                    (entity1.location() - body.location())
-                        .norm_squared()
-                        .total_cmp(&(entity2.location() - body.location()).norm_squared())
-                })
-        {
+    /// The diameter of the creature.
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// The size of the creature relative to the default entity size.
+    pub fn relative_size(&self, config: &Config) -> f32 {
+        self.size / config.entity_size()
+    }
+
+    /// The mass of the creature. A creature of the default entity size has unit mass.
+    pub fn mass(&self, config: &Config) -> f32 {
+        self.relative_size(config).powi(2)
+    }
+
+    /// The most energy the creature can store.
+    pub fn max_energy(&self, config: &Config) -> f32 {
+        config.creature_max_energy() * self.mass(config)
+    }
+
+    /// The energy above which the creature reproduces. Scales with mass like the max energy, so creatures of any
+    /// size can reproduce.
+    pub fn reproduction_energy(&self, config: &Config) -> f32 {
+        config.creature_reproduction_energy() * self.mass(config)
+    }
+
+    /// How far away the creature can see food.
+    pub fn sensing_range(&self) -> f32 {
+        self.sensing_range
+    }
+
+    /// The angle in radians of the cone in front of the creature in which it can see food.
+    pub fn field_of_view(&self) -> f32 {
+        self.field_of_view
+    }
+
+    /// The temperature the creature is most comfortable at.
+    pub fn preferred_temperature(&self) -> f32 {
+        self.preferred_temperature
+    }
+
+    /// How far from its preferred temperature the creature can live comfortably.
+    pub fn temperature_tolerance(&self) -> f32 {
+        self.temperature_tolerance
+    }
+
+    /// How uncomfortable the temperature is for the creature, as the square of its distance from the preferred
+    /// temperature in tolerances.
+    pub fn temperature_stress(&self, temperature: f32) -> f32 {
+        ((temperature - self.preferred_temperature) / self.temperature_tolerance).powi(2)
+    }
+
+    /// The direction in which the temperature gets more comfortable, scaled by how much the stress drops within
+    /// the sensing range.
+    ///
+    /// Like food, the temperature is only sensed within the field of view: at the center and both edges of the cone,
+    /// or all around for creatures that see all around.
+    fn comfort_gradient(&self, state: &State, location: Location) -> Vector {
+        let stress = |location| {
+            self.temperature_stress(
+                state.environment_value(EnvironmentField::Temperature, location),
+            )
+        };
+        let here = stress(location);
+        let probes = if self.field_of_view >= TAU { 4 } else { 3 };
+        (0..probes)
+            .map(|probe| {
+                let angle = if self.field_of_view >= TAU {
+                    self.heading + probe as f32 * FRAC_PI_2
+                } else {
+                    self.heading + (probe as f32 - 1.) * 0.5 * self.field_of_view
+                };
+                let direction = Vector::new(angle.cos(), angle.sin());
+                direction * (here - stress(location + direction * self.sensing_range))
+            })
+            .sum::<Vector>()
+            * (2. / probes as f32)
+    }
+
+    /// The direction the creature is facing as an angle in radians.
+    pub fn heading(&self) -> f32 {
+        self.heading
+    }
+
+    /// The value of the given heritable trait.
+    pub fn trait_value(&self, creature_trait: CreatureTrait) -> f32 {
+        match creature_trait {
+            CreatureTrait::MaxAcceleration => self.max_acceleration,
+            CreatureTrait::Size => self.size,
+            CreatureTrait::SensingRange => self.sensing_range,
+            CreatureTrait::FieldOfView => self.field_of_view,
+            CreatureTrait::PreferredTemperature => self.preferred_temperature,
+            CreatureTrait::TemperatureTolerance => self.temperature_tolerance,
+            CreatureTrait::Lifespan => self.lifespan,
+        }
+    }
+
+    /// Seconds since the creature was born.
+    pub fn age(&self) -> f32 {
+        self.age
+    }
+
+    /// Seconds the creature lives if it dies of old age by lifespan. Scales how fast it ages otherwise.
+    pub fn lifespan(&self) -> f32 {
+        self.lifespan
+    }
+
+    /// Whether the creature is old enough to reproduce.
+    pub fn is_mature(&self, config: &Config) -> bool {
+        self.age >= config.aging.maturity_age
+    }
+
+    /// Whether the creature dies of old age at its current age. Random with Gompertz mortality.
+    pub fn dies_of_old_age(&self, config: &Config, rng: &mut impl Rng) -> bool {
+        let relative_lifespan = self.lifespan / config.aging.starting_lifespan;
+        match config.aging.mortality {
+            Mortality::Ageless => false,
+            Mortality::Lifespan => self.age >= self.lifespan,
+            Mortality::Gompertz {
+                base_rate,
+                doubling_time,
+            } => {
+                let rate = base_rate / relative_lifespan
+                    * 2f32.powf(self.age / (doubling_time * relative_lifespan));
+                rng.gen::<f32>() < -(-rate * config.tick_length()).exp_m1()
+            }
+        }
+    }
+
+    pub fn lineage(&self) -> &Lineage {
+        &self.lineage
+    }
+
+    /// The food the creature went for during the last tick. `None` if it was wandering.
+    pub fn target(&self) -> Option<EntityId> {
+        self.target
+    }
+
+    /// The area the creature can sense.
+    pub fn sensed_area(&self) -> f32 {
+        0.5 * self.field_of_view * self.sensing_range.powi(2)
+    }
+
+    /// Whether the creature at `location` can see something at `target`.
+    pub fn can_see(&self, location: Location, target: Location) -> bool {
+        let delta = target - location;
+        let distance = delta.norm();
+        if distance > self.sensing_range {
+            return false;
+        }
+        if distance == 0. || self.field_of_view >= TAU {
+            return true;
+        }
+        let heading = Vector::new(self.heading.cos(), self.heading.sin());
+        delta.dot(&heading) / distance >= (self.field_of_view * 0.5).cos()
+    }
+
+    /// The energy spent on metabolism during the last tick.
+    pub fn metabolic_cost(&self) -> MetabolicCost {
+        self.metabolic_cost
+    }
+
+    pub fn tick(&self, body: &mut PhysicsBody, state: &State, rng: &mut impl Rng) -> Self {
+        let config = state.config();
+        let (force, heading, target) = if let Some(food) = state
+            .entities()
+            .filter(|&entity| entity.is_food())
+            .filter(|&entity| self.can_see(body.location(), entity.location()))
+            .min_by(|&entity1, &entity2| {
+                (entity1.location() - body.location())
+                    .norm_squared()
+                    .total_cmp(&(entity2.location() - body.location()).norm_squared())
+            }) {
             let target_location = food.location();
 
             let target_delta = target_location - body.location();
             let cur_velocity = body.velocity();
             let target_acceleration = target_delta - cur_velocity;
-            let norm_acceleration = target_acceleration.normalize() * self.max_acceleration;
-
-            body.accelerate(state.config(), norm_acceleration);
-        }
+            let force = target_acceleration.normalize() * self.max_acceleration;
+            let heading = force.y.atan2(force.x);
+            (force, heading, Some(food.id()))
+        } else {
+            // Nothing in sight, so wander around in a random walk, turning towards a more comfortable temperature.
+            let turn = Normal::new(
+                0.,
+                config.creature_wander_turn_rate() * config.tick_length().sqrt(),
+            )
+            .unwrap(); // Turn rate and tick length are both positive.
+            let comfort = if CreatureTrait::PreferredTemperature.is_selected(config) {
+                self.comfort_gradient(state, body.location())
+            } else {
+                Vector::zeros()
+            };
+            let steer = if comfort.norm() > 0. {
+                let towards_comfort =
+                    (comfort.y.atan2(comfort.x) - self.heading + PI).rem_euclid(TAU) - PI;
+                towards_comfort
+                    * (comfort.norm().min(1.)
+                        * config.creature_wander_turn_rate()
+                        * config.tick_length())
+                    .min(1.)
+            } else {
+                0.
+            };
+            let heading = (self.heading + steer + rng.sample(turn) + PI).rem_euclid(TAU) - PI;
+            let force = Vector::new(heading.cos(), heading.sin())
+                * self.max_acceleration
+                * config.creature_wander_effort();
+            (force, heading, None)
+        };
+        body.apply_force(force, self.mass(config));
 
+        let temperature = state.environment_value(EnvironmentField::Temperature, body.location());
+        let metabolic_cost = self.calculate_metabolic_cost(config, body, force, temperature);
         Self {
-            energy: self.energy - 0.025 * self.max_acceleration * state.config().tick_length(),
+            energy: self.energy - metabolic_cost.total(),
+            heading,
+            age: self.age + config.tick_length(),
+            target,
+            metabolic_cost,
             ..self.clone()
         }
     }
 
-    pub fn eat(&self, config: &Config, energy: f32) -> Self {
-        let max_energy = config.creature_max_energy();
-        Self {
-            energy: self.energy + energy * (1. - (self.energy / max_energy).powi(2)),
+    fn calculate_metabolic_cost(
+        &self,
+        config: &Config,
+        body: &PhysicsBody,
+        force: Vector,
+        temperature: f32,
+    ) -> MetabolicCost {
+        let metabolism = &config.metabolism;
+        let tick_length = config.tick_length();
+        let relative_size = self.relative_size(config);
+        let mass = self.mass(config);
+
+        let maintenance = if config.aging.mortality == Mortality::Ageless {
+            0.
+        } else {
+            metabolism.lifespan_rate * self.lifespan / config.aging.starting_lifespan
+        };
+        let basal = (metabolism.basal_rate * relative_size.powf(metabolism.basal_size_exponent)
+            + maintenance)
+            * tick_length;
+        let kinetic_energy = 0.5 * mass * body.velocity().norm_squared();
+        let movement = (metabolism.acceleration_rate * force.norm()
+            + metabolism.kinetic_energy_rate * kinetic_energy)
+            * tick_length;
+        let reference_sensed_area = 0.5
+            * config.creature_starting_field_of_view()
+            * config.creature_starting_sensing_range().powi(2);
+        let sensing =
+            metabolism.sensing_rate * self.sensed_area() / reference_sensed_area * tick_length;
+        // Tolerance only pays off where the temperature varies.
+        let tolerance = if CreatureTrait::TemperatureTolerance.is_selected(config) {
+            metabolism.tolerance_rate * self.temperature_tolerance
+                / config.creature_starting_temperature_tolerance()
+        } else {
+            0.
+        };
+        let thermoregulation = (metabolism.temperature_rate * self.temperature_stress(temperature)
+            + tolerance)
+            * tick_length;
+        MetabolicCost::new(basal, movement, sensing, thermoregulation)
+    }
+
+    /// Eats food with the given energy, returning the fed creature and the energy it could not absorb.
+    ///
+    /// The closer the creature is to its max energy, the less of the food it absorbs.
+    pub fn eat(&self, config: &Config, energy: f32) -> (Self, f32) {
+        let max_energy = self.max_energy(config);
+        let absorbed = energy * (1. - (self.energy / max_energy).powi(2)).max(0.);
+        let fed = Self {
+            energy: self.energy + absorbed,
             ..self.clone()
-        }
+        };
+        (fed, energy - absorbed)
+    }
+
+    /// The food left behind when this creature dies.
+    ///
+    /// Holds a fraction of the creature's body energy, which scales with its mass, and whatever energy it had stored.
+    /// Returns `None` if carcasses are disabled.
+    pub fn carcass(&self, config: &Config) -> Option<Food> {
+        let body_energy = config.creature_body_energy() * self.mass(config);
+        config
+            .creature_carcass_energy_fraction()
+            .map(|fraction| Food::with_energy(fraction * (body_energy + self.energy.max(0.))))
     }
 
-    pub fn reproduce(&self, config: &Config, rng: &mut impl Rng) -> Option<(Self, Self)> {
-        if self.energy > config.creature_reproduction_energy() {
-            let child_energy = self.energy / 2.;
-            let log_normal = Normal::new(0., 0.1).unwrap().map(|x: f32| x.exp());
-            Some((
-                Self {
-                    energy: child_energy,
-                    max_acceleration: self.max_acceleration * rng.sample(&log_normal),
+    /// Reproduces if the creature is mature and has enough energy, returning the creature if it survives, its
+    /// children and the energy the reproduction cost.
+    ///
+    /// The reproduction cost is paid first. The children share the rest of the energy equally under fission and the
+    /// invested fraction of it under budding. `id` is the id of the reproducing creature.
+    pub fn reproduce(
+        &self,
+        config: &Config,
+        id: EntityId,
+        rng: &mut impl Rng,
+    ) -> Option<(Option<Self>, Vec<Self>, f32)> {
+        if self.is_mature(config) && self.energy > self.reproduction_energy(config) {
+            let reproduction = &config.reproduction;
+            let available = (self.energy - reproduction.cost).max(0.);
+            let cost = self.energy - available;
+            let (parent, invested) = match reproduction.mode {
+                ReproductionMode::Fission => (None, available),
+                ReproductionMode::Budding => {
+                    let invested = available * reproduction.investment.clamp(0., 1.);
+                    let parent = Self {
+                        energy: available - invested,
+                        ..self.clone()
+                    };
+                    (Some(parent), invested)
+                }
+            };
+            let child_energy = invested / reproduction.litter_size as f32;
+            let temperature_varies = CreatureTrait::PreferredTemperature.is_selected(config);
+            let aging = CreatureTrait::Lifespan.is_selected(config);
+            let normal = Normal::new(0., 0.1).unwrap();
+            let log_normal = normal.map(|x: f32| x.exp());
+            let mut child = || Self {
+                energy: child_energy,
+                max_acceleration: self.max_acceleration * rng.sample(&log_normal),
+                size: self.size * rng.sample(&log_normal),
+                sensing_range: self.sensing_range * rng.sample(&log_normal),
+                field_of_view: (self.field_of_view * rng.sample(&log_normal)).min(TAU),
+                // Temperatures can be zero or negative, so the preferred temperature mutates by a fraction of the
+                // tolerance instead of a factor.
+                preferred_temperature: if temperature_varies {
+                    self.preferred_temperature + rng.sample(normal) * self.temperature_tolerance
+                } else {
+                    self.preferred_temperature
                 },
-                Self {
-                    energy: child_energy,
-                    max_acceleration: self.max_acceleration * rng.sample(log_normal),
+                temperature_tolerance: if temperature_varies {
+                    self.temperature_tolerance * rng.sample(&log_normal)
+                } else {
+                    self.temperature_tolerance
                 },
-            ))
+                lifespan: if aging {
+                    self.lifespan * rng.sample(&log_normal)
+                } else {
+                    self.lifespan
+                },
+                heading: rng.gen_range(-PI..PI),
+                age: 0.,
+                lineage: self.lineage.child(id),
+                target: None,
+                metabolic_cost: MetabolicCost::default(),
+            };
+            let children = (0..reproduction.litter_size).map(|_| child()).collect();
+            Some((parent, children, cost))
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64Mcg;
+
+    use super::*;
+    use crate::world::{EntityIdGenerator, FieldLayout};
+
+    #[test]
+    pub fn budding_keeps_parent_and_pays_cost() {
+        let mut config = Config::default();
+        config.reproduction.mode = ReproductionMode::Budding;
+        config.reproduction.litter_size = 3;
+        config.reproduction.investment = 0.6;
+        config.reproduction.cost = 10.;
+        let id = EntityIdGenerator::new().next_id();
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        let creature = Creature {
+            energy: 70.,
+            ..Creature::new(&config, Lineage::founder(id), &mut rng)
+        };
+        let (parent, children, cost) = creature.reproduce(&config, id, &mut rng).unwrap();
+        assert_eq!(cost, 10.);
+        assert!((parent.unwrap().energy() - 24.).abs() < 1e-4);
+        assert_eq!(children.len(), 3);
+        for child in &children {
+            assert!((child.energy() - 12.).abs() < 1e-4);
+            assert_eq!(child.age(), 0.);
+        }
+
+        config.reproduction.mode = ReproductionMode::Fission;
+        let (parent, children, _) = creature.reproduce(&config, id, &mut rng).unwrap();
+        assert!(parent.is_none());
+        assert!(children
+            .iter()
+            .all(|child| (child.energy() - 20.).abs() < 1e-4));
+    }
+
+    #[test]
+    pub fn can_see_within_cone_across_wrap_around() {
+        let config = Config::default();
+        let id = EntityIdGenerator::new().next_id();
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        // Facing left, just short of the angle where headings wrap from π to -π.
+        let creature = Creature {
+            heading: PI - 0.05,
+            field_of_view: 1.,
+            sensing_range: 10.,
+            ..Creature::new(&config, Lineage::founder(id), &mut rng)
+        };
+        let location = Location::new(50., 50.);
+        assert!(creature.can_see(location, Location::new(45., 50.5)));
+        assert!(creature.can_see(location, Location::new(45., 49.5)));
+        assert!(creature.can_see(location, location));
+        assert!(!creature.can_see(location, Location::new(50., 45.)));
+        assert!(!creature.can_see(location, Location::new(55., 50.)));
+        assert!(!creature.can_see(location, Location::new(35., 50.)));
+
+        let all_round = Creature {
+            field_of_view: TAU,
+            ..creature
+        };
+        assert!(all_round.can_see(location, Location::new(55., 50.)));
+        assert!(!all_round.can_see(location, Location::new(65., 50.)));
+    }
+
+    #[test]
+    pub fn wanders_without_food_in_sight() {
+        let mut config = Config::default();
+        config.set("food_spawn_rate", "0").unwrap();
+        let state = State::init(config.clone(), 0);
+        let id = EntityIdGenerator::new().next_id();
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        let mut creature = Creature {
+            heading: PI - 0.05,
+            ..Creature::new(&config, Lineage::founder(id), &mut rng)
+        };
+        let mut body = PhysicsBody::new(Location::new(50., 50.), Vector::zeros());
+        for _ in 0..100 {
+            creature = creature.tick(&mut body, &state, &mut rng);
+            assert_eq!(creature.target(), None);
+            assert!((-PI..PI).contains(&creature.heading()));
+        }
+
+        // Without turning the creature keeps pushing in the direction it is heading.
+        config.set("creature_wander_turn_rate", "0").unwrap();
+        let state = State::init(config, 0);
+        let mut body = PhysicsBody::new(Location::new(50., 50.), Vector::zeros());
+        let heading = creature.heading();
+        let creature = creature.tick(&mut body, &state, &mut rng);
+        assert_eq!(creature.heading(), heading);
+        let pushed = body
+            .tick(state.config(), state.terrain())
+            .velocity()
+            .normalize();
+        assert!((pushed - Vector::new(heading.cos(), heading.sin())).norm() < 1e-4);
+    }
+
+    #[test]
+    pub fn comfort_is_only_sensed_within_field_of_view() {
+        let mut config = Config::default();
+        config.environment.temperature.layout = FieldLayout::HorizontalGradient;
+        config.environment.temperature.amplitude = 10.;
+        let state = State::init(config.clone(), 0);
+        let id = EntityIdGenerator::new().next_id();
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        // Too warm on the right half of the world, so it is more comfortable to the left.
+        let location = Location::new(75., 50.);
+        let all_round = Creature {
+            field_of_view: TAU,
+            sensing_range: 10.,
+            ..Creature::new(&config, Lineage::founder(id), &mut rng)
+        };
+        let comfort = all_round.comfort_gradient(&state, location);
+        assert!(comfort.x < 0. && comfort.y.abs() < 1e-4, "{comfort:?}");
+
+        // Looking along the isotherm the creature barely senses the gradient at the edges of its view.
+        let sideways = Creature {
+            field_of_view: 0.5,
+            heading: FRAC_PI_2,
+            ..all_round.clone()
+        };
+        let sensed = sideways.comfort_gradient(&state, location);
+        assert!(
+            sensed.x < 0. && sensed.norm() < 0.2 * comfort.norm(),
+            "{sensed:?}"
+        );
+
+        // Looking towards the warmth the creature senses that it gets worse ahead.
+        let away = Creature {
+            heading: 0.,
+            ..sideways
+        };
+        assert!(away.comfort_gradient(&state, location).x < 0.);
+    }
+
+    #[test]
+    pub fn lifespan_only_mutates_with_mortality() {
+        let mut config = Config::default();
+        let id = EntityIdGenerator::new().next_id();
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        let creature = Creature {
+            energy: 70.,
+            ..Creature::new(&config, Lineage::founder(id), &mut rng)
+        };
+        let (_, children, _) = creature.reproduce(&config, id, &mut rng).unwrap();
+        assert!(children
+            .iter()
+            .all(|child| child.lifespan() == creature.lifespan()));
+
+        config.aging.mortality = Mortality::Lifespan;
+        let (_, children, _) = creature.reproduce(&config, id, &mut rng).unwrap();
+        assert!(children
+            .iter()
+            .all(|child| child.lifespan() != creature.lifespan()));
+    }
+
+    #[test]
+    pub fn energy_scales_with_mass() {
+        let config = Config::default();
+        let id = EntityIdGenerator::new().next_id();
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        let small = Creature {
+            size: 0.5 * config.entity_size(),
+            energy: 20.,
+            ..Creature::new(&config, Lineage::founder(id), &mut rng)
+        };
+        assert_eq!(small.mass(&config), 0.25);
+        assert_eq!(
+            small.max_energy(&config),
+            0.25 * config.creature_max_energy()
+        );
+        assert_eq!(
+            small.reproduction_energy(&config),
+            0.25 * config.creature_reproduction_energy()
+        );
+        // Small creatures can store enough energy to reproduce.
+        assert!(small.reproduction_energy(&config) < small.max_energy(&config));
+        assert!(small.reproduce(&config, id, &mut rng).is_some());
+    }
+
+    #[test]
+    pub fn metabolic_costs_scale_as_documented() {
+        let config = Config::default();
+        let metabolism = &config.metabolism;
+        let tick_length = config.tick_length();
+        let id = EntityIdGenerator::new().next_id();
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        let creature = Creature::new(&config, Lineage::founder(id), &mut rng);
+        let body = PhysicsBody::new(Location::ORIGIN, Vector::zeros());
+        let temperature = creature.preferred_temperature;
+        let cost = |creature: &Creature, force: Vector| {
+            creature.calculate_metabolic_cost(&config, &body, force, temperature)
+        };
+        let close = |a: f32, b: f32| (a - b).abs() <= 1e-6 * b.abs().max(1e-3);
+
+        let reference = cost(&creature, Vector::new(3., 4.));
+        assert!(close(
+            reference.basal(),
+            metabolism.basal_rate * tick_length
+        ));
+        assert!(close(
+            reference.movement(),
+            5. * metabolism.acceleration_rate * tick_length
+        ));
+        assert!(close(
+            reference.sensing(),
+            metabolism.sensing_rate * tick_length
+        ));
+
+        // Basal metabolism grows with the relative size to the power of the size exponent.
+        let large = Creature {
+            size: 2. * creature.size,
+            ..creature.clone()
+        };
+        assert!(close(
+            cost(&large, Vector::zeros()).basal(),
+            2f32.powf(metabolism.basal_size_exponent) * reference.basal()
+        ));
+        // Movement is proportional to the force.
+        assert!(close(
+            cost(&creature, Vector::new(6., 8.)).movement(),
+            2. * reference.movement()
+        ));
+        // Sensing is proportional to the sensed area, i.e. the field of view times the squared range.
+        let far_sighted = Creature {
+            sensing_range: 2. * creature.sensing_range,
+            field_of_view: 0.5 * creature.field_of_view,
+            ..creature.clone()
+        };
+        assert!(close(
+            cost(&far_sighted, Vector::zeros()).sensing(),
+            2. * reference.sensing()
+        ));
+    }
+
+    #[test]
+    pub fn tolerance_is_charged_only_where_temperature_varies() {
+        let mut config = Config::default();
+        let id = EntityIdGenerator::new().next_id();
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        let creature = Creature {
+            temperature_tolerance: 2. * config.creature_starting_temperature_tolerance(),
+            ..Creature::new(&config, Lineage::founder(id), &mut rng)
+        };
+        let body = PhysicsBody::new(Location::ORIGIN, Vector::zeros());
+        let thermoregulation = |config: &Config, temperature| {
+            creature
+                .calculate_metabolic_cost(config, &body, Vector::zeros(), temperature)
+                .thermoregulation()
+        };
+        let comfortable = creature.preferred_temperature;
+        assert_eq!(thermoregulation(&config, comfortable), 0.);
+
+        config.environment.temperature.layout = FieldLayout::HorizontalGradient;
+        config.environment.temperature.amplitude = 10.;
+        let metabolism = &config.metabolism;
+        let tolerance_cost = 2. * metabolism.tolerance_rate * config.tick_length();
+        assert!((thermoregulation(&config, comfortable) - tolerance_cost).abs() < 1e-6);
+        // Stress grows with the square of the distance from the preferred temperature in tolerances.
+        let stressed = comfortable + 2. * creature.temperature_tolerance;
+        let stress_cost = 4. * metabolism.temperature_rate * config.tick_length();
+        assert!((thermoregulation(&config, stressed) - stress_cost - tolerance_cost).abs() < 1e-6);
+    }
+}
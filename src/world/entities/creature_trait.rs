@@ -0,0 +1,53 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::{config::Mortality, Config};
+
+/// The heritable traits of a creature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter)]
+pub enum CreatureTrait {
+    MaxAcceleration,
+    Size,
+    SensingRange,
+    FieldOfView,
+    PreferredTemperature,
+    TemperatureTolerance,
+    Lifespan,
+}
+
+impl CreatureTrait {
+    /// The position of the trait in [`CreatureTrait::iter`](strum::IntoEnumIterator::iter).
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Whether selection acts on the trait with the given config.
+    ///
+    /// Other traits would only drift, so they are neither mutated nor used to tell species apart.
+    pub fn is_selected(&self, config: &Config) -> bool {
+        match self {
+            CreatureTrait::PreferredTemperature | CreatureTrait::TemperatureTolerance => {
+                !config.environment.temperature.is_constant()
+            }
+            CreatureTrait::Lifespan => config.aging.mortality != Mortality::Ageless,
+            _ => true,
+        }
+    }
+}
+
+impl Display for CreatureTrait {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CreatureTrait::MaxAcceleration => "Max acc.",
+            CreatureTrait::Size => "Size",
+            CreatureTrait::SensingRange => "Sensing range",
+            CreatureTrait::FieldOfView => "Field of view",
+            CreatureTrait::PreferredTemperature => "Preferred temp.",
+            CreatureTrait::TemperatureTolerance => "Temp. tolerance",
+            CreatureTrait::Lifespan => "Lifespan",
+        };
+        write!(f, "{name}")
+    }
+}
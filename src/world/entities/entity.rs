@@ -1,4 +1,4 @@
-use std::f32::consts::PI;
+use std::f32::consts::{PI, TAU};
 
 use itertools::Either;
 use rand::Rng;
@@ -10,29 +10,37 @@ use crate::{
     Config, State,
 };
 
-use super::creature::Creature;
+use super::{creature::Creature, food::Food, EntityId, EntityIdGenerator, Lineage};
 
 #[derive(Debug, Clone)]
 pub struct Entity {
+    id: EntityId,
     body: PhysicsBody,
     data: EntityData,
 }
 
 impl Entity {
-    pub fn food(location: Location) -> Self {
+    pub fn food(config: &Config, id: EntityId, location: Location) -> Self {
         Self {
+            id,
             body: PhysicsBody::new(location, Vector::new(0.0, 0.0)),
-            data: EntityData::food(),
+            data: EntityData::food(config),
         }
     }
 
-    pub fn creature(config: &Config, location: Location) -> Self {
+    /// A founder creature facing a random direction.
+    pub fn creature(config: &Config, id: EntityId, location: Location, rng: &mut impl Rng) -> Self {
         Self {
+            id,
             body: PhysicsBody::new(location, Vector::new(0.0, 0.0)),
-            data: EntityData::creature(config),
+            data: EntityData::creature(config, Lineage::founder(id), rng),
         }
     }
 
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
     pub fn entity_data(&self) -> &EntityData {
         &self.data
     }
@@ -45,58 +53,125 @@ impl Entity {
         self.body.location()
     }
 
+    pub fn velocity(&self) -> Vector {
+        self.body.velocity()
+    }
+
+    /// The energy held by the entity, i.e. the energy stored in a creature or the energy in food.
+    pub fn energy(&self) -> f32 {
+        match &self.data {
+            EntityData::Creature(creature) => creature.energy(),
+            EntityData::Food(food) => food.energy(),
+        }
+    }
+
+    /// The diameter of the entity, used both for eating and drawing.
+    pub fn size(&self, config: &Config) -> f32 {
+        match &self.data {
+            EntityData::Creature(creature) => creature.size(),
+            EntityData::Food(_) => config.entity_size(),
+        }
+    }
+
     pub fn is_food(&self) -> bool {
-        matches!(self.data, EntityData::Food)
+        matches!(self.data, EntityData::Food(_))
     }
 
     pub fn is_creature(&self) -> bool {
         matches!(self.data, EntityData::Creature(_))
     }
 
-    pub fn tick(&self, state: &State) -> Self {
+    pub fn tick(&self, state: &State, rng: &mut impl Rng) -> Self {
         let mut body = self.body.clone();
-        let entity_type = self.data.tick(&mut body, state);
-        let body = body.tick(state);
+        let entity_type = self.data.tick(&mut body, state, rng);
+        let body = body.tick(state.config(), state.terrain());
         Self {
+            id: self.id,
             body,
             data: entity_type,
         }
     }
 
-    pub fn eat(&self, config: &Config, energy: f32) -> Self {
+    /// Feeds the creature, returning it fed and the energy it could not absorb.
+    pub fn eat(&self, config: &Config, energy: f32) -> (Self, f32) {
+        match &self.data {
+            EntityData::Creature(creature) => {
+                let (fed, overflow) = creature.eat(config, energy);
+                let fed = Self {
+                    id: self.id,
+                    body: self.body.clone(),
+                    data: EntityData::Creature(fed),
+                };
+                (fed, overflow)
+            }
+            EntityData::Food(_) => panic!("Food cannot eat!"),
+        }
+    }
+
+    /// Returns the carcass left behind when this creature dies, if carcasses are enabled.
+    pub fn carcass(&self, config: &Config, ids: &mut EntityIdGenerator) -> Option<Self> {
         match &self.data {
-            EntityData::Creature(creature) => Self {
-                body: self.body.clone(),
-                data: EntityData::Creature(creature.eat(config, energy)),
-            },
-            EntityData::Food => panic!("Food cannot eat!"),
+            EntityData::Creature(creature) => creature.carcass(config).map(|food| Self {
+                id: ids.next_id(),
+                body: PhysicsBody::new(self.location(), Vector::new(0.0, 0.0)),
+                data: EntityData::Food(food),
+            }),
+            EntityData::Food(_) => panic!("Food cannot die!"),
         }
     }
 
-    pub fn reproduce(self, config: &Config, rng: &mut impl Rng) -> impl Iterator<Item = Self> {
+    /// Reproduces if the creature can, returning the surviving entities and the energy the reproduction cost.
+    pub fn reproduce(
+        self,
+        config: &Config,
+        rng: &mut impl Rng,
+        ids: &mut EntityIdGenerator,
+    ) -> (impl Iterator<Item = Self>, f32) {
         match &self.data {
             EntityData::Creature(creature) => {
-                if let Some((child1, child2)) = creature.reproduce(config, rng) {
-                    let child_bounce = config.creature_child_bounce()
-                        * Vector::new(rng.gen_range(-PI..PI).cos(), rng.gen_range(-PI..PI).sin());
-                    Either::Left(
-                        [
-                            Self {
-                                body: self.body.clone().add_velocity(child_bounce),
-                                data: EntityData::Creature(child1),
-                            },
-                            Self {
-                                body: self.body.add_velocity(-child_bounce),
-                                data: EntityData::Creature(child2),
-                            },
-                        ]
-                        .into_iter(),
-                    )
+                if let Some((parent, children, cost)) = creature.reproduce(config, self.id, rng) {
+                    // Children bounce off in opposite or evenly spread directions, so their momenta cancel out. A lone
+                    // bud has nothing to bounce off and stays with its parent.
+                    let bounces: Vec<_> = if children.len() == 1 {
+                        vec![Vector::zeros()]
+                    } else if children.len() == 2 {
+                        let bounce = config.creature_child_bounce()
+                            * Vector::new(
+                                rng.gen_range(-PI..PI).cos(),
+                                rng.gen_range(-PI..PI).sin(),
+                            );
+                        vec![bounce, -bounce]
+                    } else {
+                        let angle = rng.gen_range(-PI..PI);
+                        let spread = TAU / children.len() as f32;
+                        (0..children.len())
+                            .map(|index| {
+                                let direction = angle + index as f32 * spread;
+                                config.creature_child_bounce()
+                                    * Vector::new(direction.cos(), direction.sin())
+                            })
+                            .collect()
+                    };
+                    let children: Vec<_> = children
+                        .into_iter()
+                        .zip(bounces)
+                        .map(|(child, bounce)| Self {
+                            id: ids.next_id(),
+                            body: self.body.clone().add_velocity(bounce),
+                            data: EntityData::Creature(child),
+                        })
+                        .collect();
+                    let parent = parent.map(|parent| Self {
+                        id: self.id,
+                        body: self.body,
+                        data: EntityData::Creature(parent),
+                    });
+                    (Either::Left(parent.into_iter().chain(children)), cost)
                 } else {
-                    Either::Right(std::iter::once(self))
+                    (Either::Right(std::iter::once(self)), 0.)
                 }
             }
-            EntityData::Food => Either::Right(std::iter::once(self)),
+            EntityData::Food(_) => (Either::Right(std::iter::once(self)), 0.),
         }
     }
 }
@@ -107,28 +182,28 @@ impl Entity {
 #[strum_discriminants(name(EntityType))]
 pub enum EntityData {
     Creature(Creature),
-    Food,
+    Food(Food),
 }
 
 impl EntityData {
-    pub fn food() -> EntityData {
-        EntityData::Food
+    pub fn food(config: &Config) -> EntityData {
+        EntityData::Food(Food::new(config))
     }
 
-    pub fn creature(config: &Config) -> EntityData {
-        EntityData::Creature(Creature::new(config))
+    pub fn creature(config: &Config, lineage: Lineage, rng: &mut impl Rng) -> EntityData {
+        EntityData::Creature(Creature::new(config, lineage, rng))
     }
 
     pub fn entity_type(&self) -> EntityType {
         match self {
             EntityData::Creature(_) => EntityType::Creature,
-            EntityData::Food => EntityType::Food,
+            EntityData::Food(_) => EntityType::Food,
         }
     }
 
-    pub fn tick(&self, body: &mut PhysicsBody, state: &State) -> Self {
+    pub fn tick(&self, body: &mut PhysicsBody, state: &State, rng: &mut impl Rng) -> Self {
         match self {
-            EntityData::Creature(creature) => EntityData::Creature(creature.tick(body, state)),
+            EntityData::Creature(creature) => EntityData::Creature(creature.tick(body, state, rng)),
             _ => self.clone(),
         }
     }
@@ -0,0 +1,39 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+/// Uniquely identifies an entity for the duration of a simulation.
+///
+/// Ids are never reused, so an entity that has died or reproduced will never match a new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct EntityId(u64);
+
+impl EntityId {
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for EntityId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Hands out consecutive entity ids.
+#[derive(Debug, Clone, Default)]
+pub struct EntityIdGenerator {
+    next: u64,
+}
+
+impl EntityIdGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn next_id(&mut self) -> EntityId {
+        let id = EntityId(self.next);
+        self.next += 1;
+        id
+    }
+}
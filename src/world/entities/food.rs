@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Config;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Food {
+    energy: f32,
+}
+
+impl Food {
+    /// Regular food as spawned by the world.
+    pub fn new(config: &Config) -> Self {
+        Self::with_energy(config.food_energy())
+    }
+
+    /// Food holding a specific amount of energy, e.g. the carcass of a dead creature.
+    pub fn with_energy(energy: f32) -> Self {
+        Self { energy }
+    }
+
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use super::EntityId;
+
+/// Where a creature comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lineage {
+    parent: Option<EntityId>,
+    founder: EntityId,
+    generation: u32,
+}
+
+impl Lineage {
+    /// The lineage of a creature that was placed in the world rather than born.
+    pub fn founder(id: EntityId) -> Self {
+        Self {
+            parent: None,
+            founder: id,
+            generation: 0,
+        }
+    }
+
+    /// The lineage of a child of the creature with this lineage and the given id.
+    pub fn child(&self, parent: EntityId) -> Self {
+        Self {
+            parent: Some(parent),
+            founder: self.founder,
+            generation: self.generation + 1,
+        }
+    }
+
+    /// The parent of the creature. `None` for founders.
+    pub fn parent(&self) -> Option<EntityId> {
+        self.parent
+    }
+
+    /// The creature placed in the world that this creature descends from.
+    pub fn founder_id(&self) -> EntityId {
+        self.founder
+    }
+
+    /// The number of ancestors between the creature and its founder, counting the founder.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// The energy a creature spent on its metabolism during a single tick, split by category.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct MetabolicCost {
+    basal: f32,
+    movement: f32,
+    sensing: f32,
+    thermoregulation: f32,
+}
+
+impl MetabolicCost {
+    pub fn new(basal: f32, movement: f32, sensing: f32, thermoregulation: f32) -> Self {
+        Self {
+            basal,
+            movement,
+            sensing,
+            thermoregulation,
+        }
+    }
+
+    /// Cost of staying alive.
+    pub fn basal(&self) -> f32 {
+        self.basal
+    }
+
+    /// Cost of accelerating and moving.
+    pub fn movement(&self) -> f32 {
+        self.movement
+    }
+
+    /// Cost of sensing the surroundings.
+    pub fn sensing(&self) -> f32 {
+        self.sensing
+    }
+
+    /// Cost of coping with the temperature.
+    pub fn thermoregulation(&self) -> f32 {
+        self.thermoregulation
+    }
+
+    pub fn total(&self) -> f32 {
+        self.basal + self.movement + self.sensing + self.thermoregulation
+    }
+}
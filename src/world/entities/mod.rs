@@ -1,3 +1,14 @@
 mod entity;
 pub use entity::{Entity, EntityData, EntityType};
+mod entity_id;
+pub use entity_id::{EntityId, EntityIdGenerator};
 mod creature;
+pub use creature::Creature;
+mod creature_trait;
+pub use creature_trait::CreatureTrait;
+mod food;
+pub use food::Food;
+mod lineage;
+pub use lineage::Lineage;
+mod metabolism;
+pub use metabolism::MetabolicCost;
@@ -0,0 +1,138 @@
+use std::f32::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+use crate::Config;
+
+use super::Location;
+
+/// A scalar quantity that varies over the world and over time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter, Display)]
+pub enum EnvironmentField {
+    /// Creatures spend energy on living away from their preferred temperature.
+    Temperature,
+    /// Food spawns in proportion to the light.
+    Light,
+}
+
+/// How a field varies over the world, as a pattern between -1 and 1.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum FieldLayout {
+    /// The same everywhere.
+    #[default]
+    Uniform,
+    /// Rising from the left edge of the world to the right edge.
+    HorizontalGradient,
+    /// Rising from the top edge of the world to the bottom edge.
+    VerticalGradient,
+    /// Highest in the center of the world, falling towards the corners.
+    Radial,
+    /// A checkerboard of smooth hills and valleys with the given wavelength in world units.
+    Waves { wavelength: f32 },
+}
+
+impl FieldLayout {
+    /// The pattern at the location, between -1 and 1.
+    pub fn pattern(&self, config: &Config, location: Location) -> f32 {
+        let (width, height) = (config.world_width(), config.world_height());
+        match *self {
+            FieldLayout::Uniform => 0.,
+            FieldLayout::HorizontalGradient => 2. * location.x() / width - 1.,
+            FieldLayout::VerticalGradient => 2. * location.y() / height - 1.,
+            FieldLayout::Radial => {
+                let center = Location::new(0.5 * width, 0.5 * height);
+                let corner_distance = (center - Location::ORIGIN).norm();
+                1. - 2. * (location - center).norm() / corner_distance
+            }
+            FieldLayout::Waves { wavelength } => {
+                (TAU * location.x() / wavelength).sin() * (TAU * location.y() / wavelength).sin()
+            }
+        }
+    }
+}
+
+/// A scalar field: a mean value plus a spatial pattern plus a cycle over time.
+///
+/// The value at a location and time is `mean + amplitude * pattern + cycle_amplitude * sin(2π time / period)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Field {
+    pub mean: f32,
+    pub layout: FieldLayout,
+    /// How far the value varies from the mean over the world.
+    pub amplitude: f32,
+    /// How far the value varies from the mean over time.
+    pub cycle_amplitude: f32,
+    /// Seconds of one cycle, e.g. a day or a year.
+    pub period: f32,
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Self::uniform(0.)
+    }
+}
+
+impl Field {
+    /// The same value everywhere and always.
+    pub fn uniform(mean: f32) -> Self {
+        Self {
+            mean,
+            layout: FieldLayout::Uniform,
+            amplitude: 0.,
+            cycle_amplitude: 0.,
+            period: 240.,
+        }
+    }
+
+    /// The value at the location `time` seconds after the start of the simulation.
+    pub fn value(&self, config: &Config, location: Location, time: f32) -> f32 {
+        let cycle = if self.period > 0. {
+            (TAU * time / self.period).sin()
+        } else {
+            0.
+        };
+        self.mean
+            + self.amplitude * self.layout.pattern(config, location)
+            + self.cycle_amplitude * cycle
+    }
+
+    /// Whether the value is the same everywhere and always.
+    pub fn is_constant(&self) -> bool {
+        (self.amplitude == 0. || self.layout == FieldLayout::Uniform)
+            && (self.cycle_amplitude == 0. || self.period <= 0.)
+    }
+
+    /// An upper bound of the value anywhere and at any time.
+    pub fn max(&self) -> f32 {
+        self.mean + self.amplitude.abs() + self.cycle_amplitude.abs()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn field_combines_layout_and_cycle() {
+        let config = Config::default();
+        let field = Field {
+            mean: 10.,
+            layout: FieldLayout::HorizontalGradient,
+            amplitude: 4.,
+            cycle_amplitude: 2.,
+            period: 100.,
+        };
+        let left = Location::new(0., 50.);
+        let right = Location::new(config.world_width(), 50.);
+        assert!((field.value(&config, left, 0.) - 6.).abs() < 1e-5);
+        assert!((field.value(&config, right, 0.) - 14.).abs() < 1e-5);
+        assert!((field.value(&config, right, 25.) - 16.).abs() < 1e-5);
+        assert_eq!(field.max(), 16.);
+
+        let radial = FieldLayout::Radial;
+        assert!((radial.pattern(&config, Location::new(50., 50.)) - 1.).abs() < 1e-6);
+        assert!((radial.pattern(&config, Location::ORIGIN) + 1.).abs() < 1e-6);
+    }
+}
@@ -1,10 +1,12 @@
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
 
 pub type Vector = Vector2<f32>;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Location {
     vec: Vector2<f32>,
 }
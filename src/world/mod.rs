@@ -1,6 +1,13 @@
 mod location;
 pub use location::{Location, Vector};
 mod physics_body;
-pub use physics_body::PhysicsBody;
+pub use physics_body::{Integrator, PhysicsBody};
+mod terrain;
+pub use terrain::{Ground, TerrainGrid};
+mod environment;
+pub use environment::{EnvironmentField, Field, FieldLayout};
 mod entities;
-pub use entities::{Entity, EntityData, EntityType};
+pub use entities::{
+    Creature, CreatureTrait, Entity, EntityData, EntityId, EntityIdGenerator, EntityType, Food,
+    Lineage, MetabolicCost,
+};
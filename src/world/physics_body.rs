@@ -1,16 +1,91 @@
-use crate::{Config, State};
+use serde::{Deserialize, Serialize};
 
-use super::{Location, Vector};
+use crate::Config;
+
+use super::{Location, TerrainGrid, Vector};
+
+/// How a body's motion under constant acceleration and drag is integrated over a step.
+///
+/// Bodies follow `x'' = a - drag * x'`, see `theory/targeted_acceleration.tex` for the closed-form solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Integrator {
+    /// Adds the acceleration to the velocity, moves by the new velocity and then decays the velocity by the drag.
+    #[default]
+    Euler,
+    /// Updates the velocity by acceleration and drag, then moves by the new velocity.
+    SemiImplicitEuler,
+    /// The closed-form solution, exact for any step length.
+    Exact,
+    /// The classic fourth-order Runge-Kutta method.
+    Rk4,
+}
+
+impl Integrator {
+    /// Advances `location` and `velocity` by `step` seconds under constant `acceleration` and `drag`.
+    pub fn step(
+        self,
+        location: Location,
+        velocity: Vector,
+        acceleration: Vector,
+        drag: f32,
+        step: f32,
+    ) -> (Location, Vector) {
+        match self {
+            Integrator::Euler => {
+                let velocity = velocity + acceleration * step;
+                (location + velocity * step, velocity * (-drag * step).exp())
+            }
+            Integrator::SemiImplicitEuler => {
+                let velocity = velocity + (acceleration - velocity * drag) * step;
+                (location + velocity * step, velocity)
+            }
+            Integrator::Exact => {
+                let decay = (-drag * step).exp();
+                // The time integral of the decay over the step and the time integral of that.
+                let (decay_integral, decay_double_integral) = if drag > f32::EPSILON {
+                    let decay_integral = -(-drag * step).exp_m1() / drag;
+                    (decay_integral, (step - decay_integral) / drag)
+                } else {
+                    (step, 0.5 * step * step)
+                };
+                (
+                    location + velocity * decay_integral + acceleration * decay_double_integral,
+                    velocity * decay + acceleration * decay_integral,
+                )
+            }
+            Integrator::Rk4 => {
+                let derivative = |velocity: Vector| acceleration - velocity * drag;
+                let k1 = derivative(velocity);
+                let v2 = velocity + k1 * (0.5 * step);
+                let k2 = derivative(v2);
+                let v3 = velocity + k2 * (0.5 * step);
+                let k3 = derivative(v3);
+                let v4 = velocity + k3 * step;
+                let k4 = derivative(v4);
+                (
+                    location + (velocity + (v2 + v3) * 2. + v4) * (step / 6.),
+                    velocity + (k1 + (k2 + k3) * 2. + k4) * (step / 6.),
+                )
+            }
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct PhysicsBody {
     location: Location,
     velocity: Vector,
+    /// Acceleration applied during the current tick.
+    acceleration: Vector,
 }
 
 impl PhysicsBody {
     pub fn new(location: Location, velocity: Vector) -> Self {
-        Self { location, velocity }
+        Self {
+            location,
+            velocity,
+            acceleration: Vector::zeros(),
+        }
     }
 
     pub fn location(&self) -> Location {
@@ -28,17 +103,111 @@ impl PhysicsBody {
         }
     }
 
-    /// Add a acceleration vector to the bodies velocity.
-    pub fn accelerate(&mut self, config: &Config, acceleration: Vector) {
-        self.velocity += acceleration * config.tick_length();
+    /// Add an acceleration vector that acts on the body during the next tick.
+    pub fn accelerate(&mut self, acceleration: Vector) {
+        self.acceleration += acceleration;
+    }
+
+    /// Accelerate the body by applying a force to it. Heavier bodies accelerate less.
+    pub fn apply_force(&mut self, force: Vector, mass: f32) {
+        self.accelerate(force / mass);
+    }
+
+    /// Moves the body by one tick with the configured integrator and sub-steps, keeping it inside the world.
+    ///
+    /// The drag of the terrain is taken where the body is at the start of each sub-step. A body moving into an
+    /// obstacle slides along it if it can and stops otherwise.
+    pub fn tick(&self, config: &Config, terrain: &TerrainGrid) -> Self {
+        let physics = &config.physics;
+        let sub_steps = physics.sub_steps;
+        let step = config.tick_length() / sub_steps as f32;
+        let mut location = self.location;
+        let mut velocity = self.velocity;
+        for _ in 0..sub_steps {
+            let drag = terrain.drag(config, location);
+            let (next_location, next_velocity) =
+                physics
+                    .integrator
+                    .step(location, velocity, self.acceleration, drag, step);
+            let next_location = next_location.clamp(config.upper_left(), config.lower_right());
+            // Bodies that somehow ended up in an obstacle may move out of it freely.
+            (location, velocity) =
+                if terrain.is_passable(next_location) || !terrain.is_passable(location) {
+                    (next_location, next_velocity)
+                } else if terrain.is_passable(Location::new(next_location.x(), location.y())) {
+                    (
+                        Location::new(next_location.x(), location.y()),
+                        Vector::new(next_velocity.x, 0.),
+                    )
+                } else if terrain.is_passable(Location::new(location.x(), next_location.y())) {
+                    (
+                        Location::new(location.x(), next_location.y()),
+                        Vector::new(0., next_velocity.y),
+                    )
+                } else {
+                    (location, Vector::zeros())
+                };
+        }
+
+        Self::new(location, velocity)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const START: (f64, f64) = (1000., 1000.);
+    const VELOCITY: (f64, f64) = (3., 1.);
+    const ACCELERATION: (f64, f64) = (2., -1.);
+    const DRAG: f64 = 0.5;
+    const SECONDS: f64 = 10.;
+
+    /// The closed-form location at time `t` from `theory/targeted_acceleration.tex`.
+    fn closed_form(t: f64) -> (f64, f64) {
+        let alpha = -(1. - (-DRAG * t).exp()) / DRAG;
+        let x = |x0: f64, v0: f64, a: f64| x0 - alpha * v0 + (t + alpha) / DRAG * a;
+        (
+            x(START.0, VELOCITY.0, ACCELERATION.0),
+            x(START.1, VELOCITY.1, ACCELERATION.1),
+        )
+    }
+
+    /// The distance from the closed-form solution after integrating for `SECONDS`.
+    fn error(integrator: Integrator, sub_steps: u32) -> f64 {
+        let mut config = Config::default();
+        config.set("world_width", "10000").unwrap();
+        config.set("world_height", "10000").unwrap();
+        config.set("drag", &DRAG.to_string()).unwrap();
+        config.physics.integrator = integrator;
+        config.physics.sub_steps = sub_steps;
+        let mut body = PhysicsBody::new(
+            Location::new(START.0 as f32, START.1 as f32),
+            Vector::new(VELOCITY.0 as f32, VELOCITY.1 as f32),
+        );
+        let terrain = TerrainGrid::open(&config);
+        let ticks = (SECONDS / config.tick_length() as f64).round() as usize;
+        for _ in 0..ticks {
+            body.accelerate(Vector::new(ACCELERATION.0 as f32, ACCELERATION.1 as f32));
+            body = body.tick(&config, &terrain);
+        }
+        let (x, y) = closed_form(ticks as f64 * config.tick_length() as f64);
+        ((body.location().x() as f64 - x).powi(2) + (body.location().y() as f64 - y).powi(2)).sqrt()
     }
 
-    pub fn tick(&self, state: &State) -> Self {
-        let config = state.config();
-        let location = (self.location + self.velocity * config.tick_length())
-            .clamp(config.upper_left(), config.lower_right());
-        let velocity = self.velocity * (-config.drag() * config.tick_length()).exp();
+    #[test]
+    pub fn exact_and_rk4_match_closed_form() {
+        assert!(error(Integrator::Exact, 1) < 1e-2);
+        assert!(error(Integrator::Rk4, 1) < 1e-2);
+        assert!(error(Integrator::Exact, 1) < 0.01 * error(Integrator::Euler, 1));
+    }
 
-        Self { location, velocity }
+    #[test]
+    pub fn euler_converges_with_sub_steps() {
+        for integrator in [Integrator::Euler, Integrator::SemiImplicitEuler] {
+            let coarse = error(integrator, 1);
+            let fine = error(integrator, 16);
+            assert!(fine < coarse / 4., "{integrator:?}: {fine} vs {coarse}");
+        }
     }
 }
@@ -0,0 +1,227 @@
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumCount, EnumIter, IntoEnumIterator};
+
+use crate::Config;
+
+use super::Location;
+
+/// Added to the seed of the world for the terrain, so terrain and simulation draw different random numbers.
+const TERRAIN_SEED_OFFSET: u64 = 0x7e55a1;
+
+/// Most random locations tried when looking for passable ground.
+const MAX_PLACEMENT_ATTEMPTS: usize = 1000;
+
+/// What a terrain cell is made of.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter, EnumCount, Display,
+)]
+pub enum Ground {
+    /// Has the drag of the world.
+    #[default]
+    Open,
+    Mud,
+    Water,
+    /// Cannot be entered.
+    Obstacle,
+}
+
+/// A grid of ground cells covering the world.
+#[derive(Debug, Clone)]
+pub struct TerrainGrid {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    /// Row-major cells.
+    cells: Vec<Ground>,
+}
+
+impl TerrainGrid {
+    /// Open ground everywhere.
+    pub fn open(config: &Config) -> Self {
+        let cell_size = config.terrain.cell_size;
+        let columns = ((config.world_width() / cell_size).ceil() as usize).max(1);
+        let rows = ((config.world_height() / cell_size).ceil() as usize).max(1);
+        Self {
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Ground::Open; columns * rows],
+        }
+    }
+
+    /// Random terrain with the fractions of ground in the config. The same config always generates the same terrain.
+    pub fn generate(config: &Config) -> Self {
+        let settings = &config.terrain;
+        let mut terrain = Self::open(config);
+        let fractions = [
+            (Ground::Obstacle, settings.obstacle_fraction),
+            (Ground::Water, settings.water_fraction),
+            (Ground::Mud, settings.mud_fraction),
+        ];
+        if fractions.iter().all(|&(_, fraction)| fraction <= 0.) {
+            return terrain;
+        }
+        let mut rng = Pcg64Mcg::seed_from_u64(config.rng_seed().wrapping_add(TERRAIN_SEED_OFFSET));
+        for cell in terrain.cells.iter_mut() {
+            let mut sample: f32 = rng.gen();
+            *cell = fractions
+                .iter()
+                .find(|&&(_, fraction)| {
+                    sample -= fraction;
+                    sample < 0.
+                })
+                .map_or(Ground::Open, |&(ground, _)| ground);
+        }
+        for _ in 0..settings.smoothing {
+            terrain.smooth();
+        }
+        terrain
+    }
+
+    /// Turns every cell into the most common ground around it, keeping its own ground on ties.
+    fn smooth(&mut self) {
+        let mut cells = self.cells.clone();
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let mut counts = [0; Ground::COUNT];
+                for neighbor_row in row.saturating_sub(1)..(row + 2).min(self.rows) {
+                    for neighbor_column in column.saturating_sub(1)..(column + 2).min(self.columns)
+                    {
+                        counts[self.ground(neighbor_column, neighbor_row) as usize] += 1;
+                    }
+                }
+                let own = self.ground(column, row);
+                let most_common = Ground::iter()
+                    .max_by_key(|&ground| counts[ground as usize])
+                    .unwrap(); // There is at least one kind of ground.
+                if counts[most_common as usize] > counts[own as usize] {
+                    cells[row * self.columns + column] = most_common;
+                }
+            }
+        }
+        self.cells = cells;
+    }
+
+    /// The width and height of a cell in world units.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The ground of the cell in the given column and row.
+    pub fn ground(&self, column: usize, row: usize) -> Ground {
+        self.cells[row * self.columns + column]
+    }
+
+    pub fn set_ground(&mut self, column: usize, row: usize, ground: Ground) {
+        self.cells[row * self.columns + column] = ground;
+    }
+
+    /// The ground at the location. Locations outside the world are on the nearest cell.
+    pub fn ground_at(&self, location: Location) -> Ground {
+        let column = ((location.x() / self.cell_size).max(0.) as usize).min(self.columns - 1);
+        let row = ((location.y() / self.cell_size).max(0.) as usize).min(self.rows - 1);
+        self.ground(column, row)
+    }
+
+    pub fn is_passable(&self, location: Location) -> bool {
+        self.ground_at(location) != Ground::Obstacle
+    }
+
+    /// The drag at the location.
+    pub fn drag(&self, config: &Config, location: Location) -> f32 {
+        match self.ground_at(location) {
+            Ground::Open | Ground::Obstacle => config.drag(),
+            Ground::Mud => config.terrain.mud_drag,
+            Ground::Water => config.terrain.water_drag,
+        }
+    }
+
+    /// A random passable location in the world. `None` if the whole world is impassable.
+    ///
+    /// Uniform over the passable ground. If many random attempts miss, picks a random passable cell instead, which
+    /// favors cells cut off by the edge of the world.
+    pub fn random_location(&self, config: &Config, rng: &mut impl Rng) -> Option<Location> {
+        let (width, height) = (config.world_width(), config.world_height());
+        for _ in 0..MAX_PLACEMENT_ATTEMPTS {
+            let location = Location::new(rng.gen_range(0.0..width), rng.gen_range(0.0..height));
+            if self.is_passable(location) {
+                return Some(location);
+            }
+        }
+        let passable: Vec<_> = (0..self.cells.len())
+            .filter(|&index| self.cells[index] != Ground::Obstacle)
+            .collect();
+        let index = *passable.get(rng.gen_range(0..passable.len().max(1)))?;
+        let (column, row) = ((index % self.columns) as f32, (index / self.columns) as f32);
+        let x = rng.gen_range(column * self.cell_size..((column + 1.) * self.cell_size).min(width));
+        let y = rng.gen_range(row * self.cell_size..((row + 1.) * self.cell_size).min(height));
+        Some(Location::new(x, y))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::world::{PhysicsBody, Vector};
+
+    #[test]
+    pub fn terrain_is_generated_deterministically() {
+        let mut config = Config::default();
+        config.terrain.mud_fraction = 0.3;
+        config.terrain.obstacle_fraction = 0.2;
+        let terrain = TerrainGrid::generate(&config);
+        assert_eq!(terrain.cells, TerrainGrid::generate(&config).cells);
+        assert!(terrain.cells.contains(&Ground::Mud));
+        assert!(terrain.cells.contains(&Ground::Obstacle));
+        assert!(!terrain.cells.contains(&Ground::Water));
+
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        for _ in 0..100 {
+            let location = terrain.random_location(&config, &mut rng).unwrap();
+            assert!(terrain.is_passable(location));
+        }
+    }
+
+    #[test]
+    pub fn random_location_finds_rare_passable_ground() {
+        let config = Config::default();
+        let mut terrain = TerrainGrid::open(&config);
+        for row in 0..terrain.rows() {
+            for column in 0..terrain.columns() {
+                terrain.set_ground(column, row, Ground::Obstacle);
+            }
+        }
+        let mut rng = Pcg64Mcg::seed_from_u64(0);
+        assert_eq!(terrain.random_location(&config, &mut rng), None);
+
+        terrain.set_ground(3, 4, Ground::Mud);
+        let location = terrain.random_location(&config, &mut rng).unwrap();
+        assert_eq!(terrain.ground_at(location), Ground::Mud);
+    }
+
+    #[test]
+    pub fn bodies_slide_along_obstacles() {
+        let config = Config::default();
+        let mut terrain = TerrainGrid::open(&config);
+        for row in 0..terrain.rows() {
+            terrain.set_ground(2, row, Ground::Obstacle);
+        }
+        let mut body = PhysicsBody::new(Location::new(7., 7.), Vector::new(10., 10.));
+        for _ in 0..100 {
+            body = body.tick(&config, &terrain);
+            assert!(terrain.is_passable(body.location()));
+        }
+        assert!(body.location().x() < 10.);
+        assert!(body.location().y() > 20.);
+    }
+}